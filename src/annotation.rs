@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+/// A point in normalized picture coordinates: (0, 0) is the top-left of the video picture and
+/// (1, 1) the bottom-right, whatever size or letterboxing it is displayed at.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// What an annotation draws. Two-point shapes span `from` to `to` in any direction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "lowercase")]
+pub enum Shape {
+    Pen { points: Vec<Point> },
+    Arrow { from: Point, to: Point },
+    Rect { from: Point, to: Point },
+    Ellipse { from: Point, to: Point },
+    Text { at: Point, text: String },
+}
+
+/// A drawing pinned to the frame it was made on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Zero-based media frame the annotation is shown on.
+    pub frame: i32,
+    /// CSS color, e.g. `#ff0000`.
+    pub color: String,
+    #[serde(flatten)]
+    pub shape: Shape,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Tool {
+    #[default]
+    Pen,
+    Arrow,
+    Rect,
+    Ellipse,
+    Text,
+}
+
+impl Tool {
+    pub const ALL: [Self; 5] = [
+        Self::Pen,
+        Self::Arrow,
+        Self::Rect,
+        Self::Ellipse,
+        Self::Text,
+    ];
+
+    /// Shape this tool starts when the pointer goes down at `at`.
+    pub fn start(self, at: Point) -> Shape {
+        match self {
+            Self::Pen => Shape::Pen { points: vec![at] },
+            Self::Arrow => Shape::Arrow { from: at, to: at },
+            Self::Rect => Shape::Rect { from: at, to: at },
+            Self::Ellipse => Shape::Ellipse { from: at, to: at },
+            Self::Text => Shape::Text {
+                at,
+                text: String::new(),
+            },
+        }
+    }
+}
+
+impl Shape {
+    /// Follow the pointer to `to` while drawing.
+    pub fn extend(&mut self, to: Point) {
+        match self {
+            Self::Pen { points } => points.push(to),
+            Self::Arrow { to: end, .. }
+            | Self::Rect { to: end, .. }
+            | Self::Ellipse { to: end, .. } => *end = to,
+            Self::Text { .. } => {}
+        }
+    }
+
+    /// Whether the shape is too small to have been meant, e.g. a click with the rect tool.
+    pub fn is_empty(&self) -> bool {
+        const MIN: f64 = 2e-3;
+        match self {
+            Self::Pen { points } => points.len() < 2,
+            Self::Arrow { from, to } | Self::Rect { from, to } | Self::Ellipse { from, to } => {
+                (from.x - to.x).abs() < MIN && (from.y - to.y).abs() < MIN
+            }
+            Self::Text { text, .. } => text.trim().is_empty(),
+        }
+    }
+}
+
+/// Where a picture of `video_width`×`video_height` lands inside a `box_width`×`box_height`
+/// element under `object-fit: contain`, as `(left, top, width, height)` in pixels.
+pub fn picture_rect(
+    box_width: f64,
+    box_height: f64,
+    video_width: f64,
+    video_height: f64,
+) -> (f64, f64, f64, f64) {
+    if video_width <= 0.0 || video_height <= 0.0 || box_width <= 0.0 || box_height <= 0.0 {
+        return (0.0, 0.0, box_width.max(0.0), box_height.max(0.0));
+    }
+    let scale = (box_width / video_width).min(box_height / video_height);
+    let (width, height) = (video_width * scale, video_height * scale);
+    (
+        (box_width - width) / 2.0,
+        (box_height - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+pub fn to_json(annotations: &[Annotation]) -> String {
+    serde_json::to_string(annotations).expect("annotations serialize")
+}
+
+pub fn from_json(json: &str) -> Result<Vec<Annotation>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picture_rect_letterboxes() {
+        // 16:9 in a 4:3 box: bars top and bottom.
+        assert_eq!(
+            picture_rect(800.0, 600.0, 1920.0, 1080.0),
+            (0.0, 75.0, 800.0, 450.0)
+        );
+        // 4:3 in a 16:9 box: bars left and right.
+        assert_eq!(
+            picture_rect(1600.0, 900.0, 640.0, 480.0),
+            (200.0, 0.0, 1200.0, 900.0)
+        );
+        // Metadata not loaded yet: fill the box.
+        assert_eq!(
+            picture_rect(800.0, 600.0, 0.0, 0.0),
+            (0.0, 0.0, 800.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let annotations = vec![
+            Annotation {
+                frame: 12,
+                color: "#ff0000".into(),
+                shape: Shape::Pen {
+                    points: vec![Point::new(0.1, 0.2), Point::new(0.3, 0.4)],
+                },
+            },
+            Annotation {
+                frame: 40,
+                color: "#00ff00".into(),
+                shape: Shape::Text {
+                    at: Point::new(0.5, 0.5),
+                    text: "fix this".into(),
+                },
+            },
+        ];
+        let json = to_json(&annotations);
+        assert!(json.contains(r#""tool":"pen""#));
+        assert_eq!(from_json(&json).unwrap(), annotations);
+    }
+
+    #[test]
+    fn degenerate_shapes_are_empty() {
+        let at = Point::new(0.5, 0.5);
+        assert!(Tool::Rect.start(at).is_empty());
+        let mut rect = Tool::Rect.start(at);
+        rect.extend(Point::new(0.6, 0.6));
+        assert!(!rect.is_empty());
+        assert!(Tool::Pen.start(at).is_empty());
+    }
+}
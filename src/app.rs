@@ -1,10 +1,15 @@
+use crate::comments::ServerCommentStore;
+use crate::components::comments_panel::CommentsPanel;
 use crate::components::video::Video;
-use leptos::logging::log;
+use crate::components::video_controller::VideoController;
+use crate::hooks::use_comments;
+use crate::pages::library::LibraryPage;
+use crate::pages::watch::WatchPage;
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
-    components::{Route, Router, Routes},
-    StaticSegment,
+    components::{Route, Router, Routes, A},
+    ParamSegment, StaticSegment,
 };
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
@@ -44,6 +49,8 @@ pub fn App() -> impl IntoView {
             <main>
                 <Routes fallback=|| "Page not found.".into_view()>
                     <Route path=StaticSegment("") view=HomePage />
+                    <Route path=StaticSegment("library") view=LibraryPage />
+                    <Route path=(StaticSegment("watch"), ParamSegment("id")) view=WatchPage />
                 </Routes>
             </main>
         </Router>
@@ -56,16 +63,33 @@ fn HomePage() -> impl IntoView {
     // Creates a reactive value to update the button
     let count = RwSignal::new(0);
     let on_click = move |_| *count.write() += 1;
+    let controller = VideoController::new();
+    let annotations = RwSignal::new(Vec::new());
+    let comments = use_comments(ServerCommentStore, "Metallborne3_.mp4".to_string());
 
     view! {
         <h1>"Welcome to Leptos!"</h1>
         <button on:click=on_click>"Click Me: " {count}</button>
+        <A href="/library">"Library"</A>
 
-        <div style="width:800px; height:400px;">
-            // <Video src="https://download.blender.org/peach/bigbuckbunny_movies/BigBuckBunny_640x360.m4v"
-            // .to_string()  fps=25.0 />
+        <div class="flex">
+            <div style="width:800px; height:400px;">
+                // <Video src="https://download.blender.org/peach/bigbuckbunny_movies/BigBuckBunny_640x360.m4v"
+                // .to_string()  fps=25.0 />
 
-            <Video src="Metallborne3_.mp4" proxy="Metallborne3_proxy.mp4" fps=25.0 />
+                <Video
+                    src="/media/Metallborne3_.mp4"
+                    proxy="/media/Metallborne3_proxy.mp4"
+                    fps=25.0
+                    controller=controller
+                    comments=comments
+                    annotations=annotations
+                    thumbnails="/api/thumbnails/Metallborne3_.mp4"
+                />
+            </div>
+            <div style="width:320px; height:400px;">
+                <CommentsPanel comments controller />
+            </div>
         </div>
     }
 }
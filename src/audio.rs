@@ -0,0 +1,149 @@
+use crate::probe::AudioTrack;
+use leptos::wasm_bindgen::JsValue;
+use serde::{Deserialize, Serialize};
+use web_sys::js_sys::Reflect;
+use web_sys::HtmlMediaElement;
+
+/// An entry of the player's audio track menu: a track of the media itself, or a separate
+/// file kept in sync with the picture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AudioSource {
+    /// Name in the menu, e.g. `Stereo mix` or `M&E`.
+    pub label: String,
+    /// BCP 47 or ISO 639-2 language code.
+    #[serde(default)]
+    pub language: String,
+    /// URL of a separate audio file. `None` for a track of `src`, which is matched by its
+    /// position among the other such entries.
+    #[serde(default)]
+    pub src: Option<String>,
+}
+
+impl From<&AudioTrack> for AudioSource {
+    /// An entry for a probed track of `src`, labelled with its channel layout and codec.
+    fn from(track: &AudioTrack) -> Self {
+        let layout = match track.channels {
+            1 => "Mono".to_string(),
+            2 => "Stereo".to_string(),
+            6 => "5.1".to_string(),
+            8 => "7.1".to_string(),
+            n => format!("{n} ch"),
+        };
+        AudioSource {
+            label: format!("{layout} ({})", track.codec),
+            language: track.language.clone().unwrap_or_default(),
+            src: None,
+        }
+    }
+}
+
+/// Position among the tracks of `src` of entry `index`; `None` for a separate file.
+pub fn embedded_index(sources: &[AudioSource], index: usize) -> Option<usize> {
+    let source = sources.get(index)?;
+    source.src.is_none().then(|| {
+        sources[..index]
+            .iter()
+            .filter(|source| source.src.is_none())
+            .count()
+    })
+}
+
+/// `media.audioTracks`, where the browser implements it.
+fn track_list(media: &HtmlMediaElement) -> Option<JsValue> {
+    Reflect::get(media, &"audioTracks".into())
+        .ok()
+        .filter(|list| !list.is_undefined() && !list.is_null())
+}
+
+fn tracks(media: &HtmlMediaElement) -> Vec<JsValue> {
+    let Some(list) = track_list(media) else {
+        return Vec::new();
+    };
+    let len = Reflect::get(&list, &"length".into())
+        .ok()
+        .and_then(|len| len.as_f64())
+        .unwrap_or(0.0) as u32;
+    (0..len)
+        .filter_map(|i| Reflect::get_u32(&list, i).ok())
+        .collect()
+}
+
+/// Whether the browser can switch between the tracks of one media element.
+pub(crate) fn can_switch_embedded(media: &HtmlMediaElement) -> bool {
+    track_list(media).is_some()
+}
+
+/// Menu entries for the tracks of `media`, as the browser describes them.
+pub(crate) fn embedded_sources(media: &HtmlMediaElement) -> Vec<AudioSource> {
+    let string = |track: &JsValue, key: &str| {
+        Reflect::get(track, &key.into())
+            .ok()
+            .and_then(|value| value.as_string())
+            .unwrap_or_default()
+    };
+    tracks(media)
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let label = string(track, "label");
+            AudioSource {
+                label: if label.is_empty() {
+                    format!("Track {}", i + 1)
+                } else {
+                    label
+                },
+                language: string(track, "language"),
+                src: None,
+            }
+        })
+        .collect()
+}
+
+/// Play track `index` of `media` and silence the rest. Returns `false` if the browser
+/// can't switch tracks, or there's no such track.
+pub(crate) fn enable_embedded(media: &HtmlMediaElement, index: usize) -> bool {
+    let tracks = tracks(media);
+    if index >= tracks.len() {
+        return false;
+    }
+    for (i, track) in tracks.iter().enumerate() {
+        let _ = Reflect::set(track, &"enabled".into(), &(i == index).into());
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(src: Option<&str>) -> AudioSource {
+        AudioSource {
+            label: String::new(),
+            language: String::new(),
+            src: src.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn embedded_positions_skip_files() {
+        let sources = [source(None), source(Some("/media/me.m4a")), source(None)];
+        assert_eq!(embedded_index(&sources, 0), Some(0));
+        assert_eq!(embedded_index(&sources, 1), None);
+        assert_eq!(embedded_index(&sources, 2), Some(1));
+        assert_eq!(embedded_index(&sources, 3), None);
+    }
+
+    #[test]
+    fn labels_probed_tracks() {
+        let track = AudioTrack {
+            codec: "lpcm".into(),
+            channels: 6,
+            sample_rate: 48000,
+            language: Some("fra".into()),
+        };
+        let source = AudioSource::from(&track);
+        assert_eq!(source.label, "5.1 (lpcm)");
+        assert_eq!(source.language, "fra");
+        assert_eq!(source.src, None);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::frame_rate::FrameRate;
+use crate::vtt::{self, Cue, VttError};
+use serde::{Deserialize, Serialize};
+
+/// A subtitle or caption file for the player's `tracks` prop.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TextTrack {
+    /// URL of a WebVTT or SubRip file; `.srt` files are converted when loaded.
+    pub src: String,
+    /// Name in the captions menu, e.g. `English (SDH)`.
+    pub label: String,
+    /// BCP 47 language tag, e.g. `en-US`.
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub kind: TrackKind,
+    /// Shown on load. The first default track wins.
+    #[serde(default)]
+    pub default: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackKind {
+    /// Dialogue, for viewers who can hear the audio.
+    #[default]
+    Subtitles,
+    /// Dialogue and sound effects, for viewers who can't.
+    Captions,
+}
+
+/// A cue snapped to the frames it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caption {
+    /// First zero-based media frame showing the cue.
+    pub frame: i32,
+    /// Last frame showing the cue.
+    pub end_frame: i32,
+    /// Cue text with markup removed; lines separated by `\n`.
+    pub text: String,
+}
+
+impl Caption {
+    pub fn contains(&self, frame: i32) -> bool {
+        (self.frame..=self.end_frame).contains(&frame)
+    }
+}
+
+/// Whether `src` names a SubRip file, going by its extension.
+fn is_srt(src: &str) -> bool {
+    let path = src.split(['?', '#']).next().unwrap_or(src);
+    path.rsplit_once('.')
+        .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case("srt"))
+}
+
+/// Parse a track fetched from `src`: SubRip for `.srt`, or when there's no WebVTT header.
+pub fn parse(text: &str, src: &str) -> Result<Vec<Cue>, VttError> {
+    match vtt::parse(text) {
+        Err(VttError::MissingHeader) => vtt::parse_srt(text),
+        _ if is_srt(src) => vtt::parse_srt(text),
+        result => result,
+    }
+}
+
+/// Snap cues to frames, sorted by first frame. A cue runs from the frame its start falls in
+/// to the frame before the one its end falls in, and covers at least one frame.
+pub fn to_captions(cues: &[Cue], rate: FrameRate) -> Vec<Caption> {
+    let mut captions: Vec<_> = cues
+        .iter()
+        .map(|cue| {
+            let frame = rate.frame_at(cue.start);
+            Caption {
+                frame,
+                end_frame: (rate.frame_at(cue.end) - 1).max(frame),
+                text: vtt::plain_text(&cue.text),
+            }
+        })
+        .collect();
+    captions.sort_by_key(|caption| caption.frame);
+    captions
+}
+
+/// Captions on screen at `frame`, earliest first.
+pub fn active(captions: &[Caption], frame: i32) -> impl Iterator<Item = &Caption> {
+    let i = captions.partition_point(|caption| caption.frame <= frame);
+    captions[..i]
+        .iter()
+        .filter(move |caption| caption.contains(frame))
+}
+
+/// Index of the track to show on load: the first marked default, if any.
+pub fn default_track(tracks: &[TextTrack]) -> Option<usize> {
+    tracks.iter().position(|track| track.default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nHi\n";
+        assert_eq!(parse(srt, "/subs/en.srt").unwrap()[0].start, 1.0);
+        assert_eq!(parse(srt, "/subs/en").unwrap()[0].start, 1.0);
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:02.000\nHi\n";
+        assert_eq!(parse(vtt, "/subs/en.vtt?v=2").unwrap()[0].end, 2.0);
+        assert!(is_srt("/subs/EN.SRT#t=1"));
+    }
+
+    #[test]
+    fn snaps_cues_to_frames() {
+        let cues = vtt::parse_srt(
+            "1\n00:00:01,000 --> 00:00:02,000\n<i>One</i>\n\n2\n00:00:00,000 --> 00:00:00,010\nBlip\n",
+        )
+        .unwrap();
+        let captions = to_captions(&cues, FrameRate::FPS_25);
+        assert_eq!(
+            captions,
+            [
+                Caption {
+                    frame: 0,
+                    end_frame: 0,
+                    text: "Blip".into()
+                },
+                Caption {
+                    frame: 25,
+                    end_frame: 49,
+                    text: "One".into()
+                },
+            ]
+        );
+        assert_eq!(active(&captions, 24).count(), 0);
+        assert_eq!(active(&captions, 25).next().unwrap().text, "One");
+        assert_eq!(active(&captions, 49).count(), 1);
+        assert_eq!(active(&captions, 50).count(), 0);
+    }
+}
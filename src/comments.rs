@@ -0,0 +1,196 @@
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// A review note pinned to a frame or frame range of one piece of media.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: u64,
+    /// Zero-based media frame the comment starts on.
+    pub frame: i32,
+    /// Last frame of a range comment; `None` for a single frame.
+    pub end_frame: Option<i32>,
+    pub author: String,
+    pub text: String,
+}
+
+/// A comment that hasn't been stored yet and so has no id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewComment {
+    pub frame: i32,
+    pub end_frame: Option<i32>,
+    pub author: String,
+    pub text: String,
+}
+
+/// Sort in timecode order, ties by range length then creation order.
+pub fn sort_comments(comments: &mut [Comment]) {
+    comments.sort_by_key(|c| (c.frame, c.end_frame.unwrap_or(c.frame), c.id));
+}
+
+pub type StoreFuture<T> = Pin<Box<dyn Future<Output = Result<T, ServerFnError>>>>;
+
+/// Backend that comments are loaded from and saved to, keyed by media (usually its `src`).
+pub trait CommentStore {
+    fn list(&self, media: &str) -> StoreFuture<Vec<Comment>>;
+    fn add(&self, media: &str, comment: NewComment) -> StoreFuture<Comment>;
+    fn remove(&self, media: &str, id: u64) -> StoreFuture<()>;
+}
+
+/// Comments kept in memory; lost on reload. Clones share the same comments.
+///
+/// Also what the server-function backend keeps on the server, provided as context by `main.rs`.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCommentStore {
+    inner: Arc<Mutex<MemoryInner>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryInner {
+    next_id: u64,
+    comments: Vec<(String, Comment)>,
+}
+
+impl MemoryCommentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comments on `media`, sorted.
+    pub fn comments(&self, media: &str) -> Vec<Comment> {
+        let inner = self.inner.lock().unwrap();
+        let mut comments: Vec<_> = inner
+            .comments
+            .iter()
+            .filter(|(m, _)| m == media)
+            .map(|(_, c)| c.clone())
+            .collect();
+        sort_comments(&mut comments);
+        comments
+    }
+
+    pub fn insert(&self, media: &str, comment: NewComment) -> Comment {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let comment = Comment {
+            id: inner.next_id,
+            frame: comment.frame,
+            end_frame: comment.end_frame,
+            author: comment.author,
+            text: comment.text,
+        };
+        inner.comments.push((media.to_string(), comment.clone()));
+        comment
+    }
+
+    /// Returns whether there was such a comment.
+    pub fn delete(&self, media: &str, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let len = inner.comments.len();
+        inner.comments.retain(|(m, c)| !(m == media && c.id == id));
+        inner.comments.len() != len
+    }
+}
+
+impl CommentStore for MemoryCommentStore {
+    fn list(&self, media: &str) -> StoreFuture<Vec<Comment>> {
+        let comments = self.comments(media);
+        Box::pin(async move { Ok(comments) })
+    }
+
+    fn add(&self, media: &str, comment: NewComment) -> StoreFuture<Comment> {
+        let comment = self.insert(media, comment);
+        Box::pin(async move { Ok(comment) })
+    }
+
+    fn remove(&self, media: &str, id: u64) -> StoreFuture<()> {
+        self.delete(media, id);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// Comments kept on the server through the server functions below.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerCommentStore;
+
+impl CommentStore for ServerCommentStore {
+    fn list(&self, media: &str) -> StoreFuture<Vec<Comment>> {
+        Box::pin(list_comments(media.to_string()))
+    }
+
+    fn add(&self, media: &str, comment: NewComment) -> StoreFuture<Comment> {
+        Box::pin(add_comment(media.to_string(), comment))
+    }
+
+    fn remove(&self, media: &str, id: u64) -> StoreFuture<()> {
+        Box::pin(remove_comment(media.to_string(), id))
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn server_store() -> Result<MemoryCommentStore, ServerFnError> {
+    use_context::<MemoryCommentStore>()
+        .ok_or_else(|| ServerFnError::new("no comment store provided to server functions"))
+}
+
+#[server]
+pub async fn list_comments(media: String) -> Result<Vec<Comment>, ServerFnError> {
+    Ok(server_store()?.comments(&media))
+}
+
+#[server]
+pub async fn add_comment(media: String, comment: NewComment) -> Result<Comment, ServerFnError> {
+    if comment.text.trim().is_empty() {
+        return Err(ServerFnError::new("comment is empty"));
+    }
+    Ok(server_store()?.insert(&media, comment))
+}
+
+#[server]
+pub async fn remove_comment(media: String, id: u64) -> Result<(), ServerFnError> {
+    if server_store()?.delete(&media, id) {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!("no comment {id}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(frame: i32, end_frame: Option<i32>) -> NewComment {
+        NewComment {
+            frame,
+            end_frame,
+            author: "a".into(),
+            text: format!("at {frame}"),
+        }
+    }
+
+    #[test]
+    fn memory_store_sorts_by_frame() {
+        let store = MemoryCommentStore::new();
+        store.insert("a.mp4", note(50, None));
+        store.insert("a.mp4", note(10, Some(20)));
+        store.insert("b.mp4", note(0, None));
+        store.insert("a.mp4", note(10, None));
+        let frames: Vec<_> = store
+            .comments("a.mp4")
+            .iter()
+            .map(|c| (c.frame, c.end_frame))
+            .collect();
+        assert_eq!(frames, [(10, None), (10, Some(20)), (50, None)]);
+    }
+
+    #[test]
+    fn memory_store_delete_is_per_media() {
+        let store = MemoryCommentStore::new();
+        let a = store.insert("a.mp4", note(1, None));
+        assert!(!store.delete("b.mp4", a.id));
+        assert!(store.delete("a.mp4", a.id));
+        assert!(store.comments("a.mp4").is_empty());
+    }
+}
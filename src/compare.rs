@@ -0,0 +1,140 @@
+/// One of the two versions being compared: A is the player's `src`, B its `compare`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Side {
+    #[default]
+    A,
+    B,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+        }
+    }
+}
+
+/// Which way a wipe splits the picture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WipeDirection {
+    /// A vertical line, left against right.
+    #[default]
+    Vertical,
+    /// A horizontal line, top against bottom.
+    Horizontal,
+}
+
+/// How the two versions share the picture.
+///
+/// The leading side is the one shown in [`Flip`](Self::Flip), and the one left of or
+/// above the split in the others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CompareMode {
+    /// The leading side alone, full frame.
+    #[default]
+    Flip,
+    /// Leading side up to a draggable line, the other beyond it.
+    Wipe(WipeDirection),
+    SideBySide,
+    /// Per-pixel absolute difference: black wherever the versions match.
+    Difference,
+}
+
+impl CompareMode {
+    pub const ALL: [Self; 5] = [
+        Self::Flip,
+        Self::Wipe(WipeDirection::Vertical),
+        Self::Wipe(WipeDirection::Horizontal),
+        Self::SideBySide,
+        Self::Difference,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Flip => "A/B",
+            Self::Wipe(WipeDirection::Vertical) => "Wipe ↔",
+            Self::Wipe(WipeDirection::Horizontal) => "Wipe ↕",
+            Self::SideBySide => "Side by side",
+            Self::Difference => "Difference",
+        }
+    }
+}
+
+/// Left edge and width of `side`'s part of the picture box, in percent.
+pub fn pane(mode: Option<CompareMode>, side: Side, leading: Side) -> (f64, f64) {
+    match mode {
+        Some(CompareMode::SideBySide) if side == leading => (0.0, 50.0),
+        Some(CompareMode::SideBySide) => (50.0, 50.0),
+        _ => (0.0, 100.0),
+    }
+}
+
+/// Whether B's layer, stacked over A, is shown. In difference mode the versions are only
+/// seen through the blend.
+pub fn shows_b(mode: Option<CompareMode>, leading: Side) -> bool {
+    match mode {
+        None | Some(CompareMode::Difference) => false,
+        Some(CompareMode::Flip) => leading == Side::B,
+        Some(CompareMode::Wipe(_) | CompareMode::SideBySide) => true,
+    }
+}
+
+/// CSS `clip-path` for B's layer, cutting it down to its side of a wipe `position` of the
+/// way across.
+pub fn clip_b(mode: Option<CompareMode>, position: f64, leading: Side) -> String {
+    let Some(CompareMode::Wipe(direction)) = mode else {
+        return "none".to_string();
+    };
+    let at = 100.0 * position.clamp(0.0, 1.0);
+    // `inset()` takes top, right, bottom, left.
+    match (direction, leading) {
+        (WipeDirection::Vertical, Side::A) => format!("inset(0 0 0 {at}%)"),
+        (WipeDirection::Vertical, Side::B) => format!("inset(0 {}% 0 0)", 100.0 - at),
+        (WipeDirection::Horizontal, Side::A) => format!("inset({at}% 0 0 0)"),
+        (WipeDirection::Horizontal, Side::B) => format!("inset(0 0 {}% 0)", 100.0 - at),
+    }
+}
+
+/// Which versions are on screen, in reading order: `B`, `A | B`, `|A − B|`.
+pub fn badge(mode: CompareMode, leading: Side) -> String {
+    let (first, second) = (leading.label(), leading.other().label());
+    match mode {
+        CompareMode::Flip => first.to_string(),
+        CompareMode::Wipe(_) | CompareMode::SideBySide => format!("{first} | {second}"),
+        CompareMode::Difference => "|A − B|".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_the_leading_side_first() {
+        let wipe = Some(CompareMode::Wipe(WipeDirection::Vertical));
+        assert_eq!(clip_b(wipe, 0.25, Side::A), "inset(0 0 0 25%)");
+        assert_eq!(clip_b(wipe, 0.25, Side::B), "inset(0 75% 0 0)");
+        let wipe = Some(CompareMode::Wipe(WipeDirection::Horizontal));
+        assert_eq!(clip_b(wipe, 2.0, Side::A), "inset(100% 0 0 0)");
+        assert_eq!(clip_b(Some(CompareMode::Flip), 0.5, Side::A), "none");
+
+        let side_by_side = Some(CompareMode::SideBySide);
+        assert_eq!(pane(side_by_side, Side::B, Side::A), (50.0, 50.0));
+        assert_eq!(pane(side_by_side, Side::B, Side::B), (0.0, 50.0));
+        assert_eq!(pane(None, Side::A, Side::B), (0.0, 100.0));
+
+        assert!(!shows_b(Some(CompareMode::Flip), Side::A));
+        assert!(shows_b(Some(CompareMode::Flip), Side::B));
+        assert!(!shows_b(Some(CompareMode::Difference), Side::B));
+        assert_eq!(badge(CompareMode::SideBySide, Side::B), "B | A");
+        assert_eq!(badge(CompareMode::Flip, Side::B), "B");
+    }
+}
@@ -0,0 +1,290 @@
+use crate::annotation::{picture_rect, Annotation, Point, Shape, Tool};
+use leptos::ev::PointerEvent;
+use leptos::prelude::*;
+use leptos::*;
+use leptos_use::{use_element_size, UseElementSizeReturn};
+
+const STROKE_WIDTH: f64 = 3.0;
+
+/// Drawings over the video picture, plus the tools to make them while `editing`.
+///
+/// Sits over the `<video>` elements and lines itself up with the `object-contain` picture,
+/// so annotations stay put through resizes, letterboxing and fullscreen. Only annotations on
+/// the current `frame` are shown.
+#[component]
+pub fn AnnotationLayer(
+    annotations: RwSignal<Vec<Annotation>>,
+    #[prop(into)] frame: Signal<i32>,
+    /// Intrinsic size of the video picture, `(0, 0)` until metadata has loaded.
+    #[prop(into)]
+    video_size: Signal<(f64, f64)>,
+    #[prop(into)] editing: Signal<bool>,
+) -> impl IntoView {
+    let box_ref = NodeRef::<html::Div>::new();
+    let picture_ref = NodeRef::<html::Div>::new();
+    let UseElementSizeReturn { width, height } = use_element_size(box_ref);
+    let rect = Memo::new(move |_| {
+        let (video_width, video_height) = video_size.get();
+        picture_rect(width.get(), height.get(), video_width, video_height)
+    });
+    let (tool, set_tool) = signal(Tool::Pen);
+    let (color, set_color) = signal("#ff3b30".to_string());
+    let (draft, set_draft) = signal(None::<Annotation>);
+
+    let to_point = move |ev: &PointerEvent| {
+        picture_ref.get().map(|el| {
+            let r = el.get_bounding_client_rect();
+            Point::new(
+                (ev.client_x() as f64 - r.left()) / r.width(),
+                (ev.client_y() as f64 - r.top()) / r.height(),
+            )
+        })
+    };
+
+    let pointer_down = move |ev: PointerEvent| {
+        let Some(at) = to_point(&ev) else {
+            return;
+        };
+        ev.stop_propagation();
+        let mut shape = tool.get().start(at);
+        if let Shape::Text { text, .. } = &mut shape {
+            match window().prompt_with_message("Text") {
+                Ok(Some(entered)) => *text = entered,
+                _ => return,
+            }
+            if !shape.is_empty() {
+                annotations.update(|list| {
+                    list.push(Annotation {
+                        frame: frame.get_untracked(),
+                        color: color.get_untracked(),
+                        shape,
+                    })
+                });
+            }
+            return;
+        }
+        if let Some(el) = picture_ref.get() {
+            let _ = el.set_pointer_capture(ev.pointer_id());
+        }
+        set_draft.set(Some(Annotation {
+            frame: frame.get_untracked(),
+            color: color.get_untracked(),
+            shape,
+        }));
+    };
+
+    let pointer_move = move |ev: PointerEvent| {
+        if draft.with(Option::is_none) {
+            return;
+        }
+        if let Some(to) = to_point(&ev) {
+            set_draft.update(|draft| {
+                if let Some(draft) = draft {
+                    draft.shape.extend(to);
+                }
+            });
+        }
+    };
+
+    let pointer_up = move |_: PointerEvent| {
+        if let Some(draft) = set_draft.try_update(Option::take).flatten() {
+            if !draft.shape.is_empty() {
+                annotations.update(|list| list.push(draft));
+            }
+        }
+    };
+
+    let undo = move |_| {
+        let frame = frame.get_untracked();
+        annotations.update(|list| {
+            if let Some(i) = list.iter().rposition(|a| a.frame == frame) {
+                list.remove(i);
+            }
+        });
+    };
+
+    let clear = move |_| {
+        let frame = frame.get_untracked();
+        annotations.update(|list| list.retain(|a| a.frame != frame));
+    };
+
+    let visible = move || {
+        let frame = frame.get();
+        let mut shown: Vec<_> =
+            annotations.with(|list| list.iter().filter(|a| a.frame == frame).cloned().collect());
+        shown.extend(draft.get());
+        shown
+    };
+
+    view! {
+        <div node_ref=box_ref class="absolute inset-0 pointer-events-none">
+            <div
+                node_ref=picture_ref
+                class="absolute"
+                style:left=move || format!("{}px", rect.get().0)
+                style:top=move || format!("{}px", rect.get().1)
+                style:width=move || format!("{}px", rect.get().2)
+                style:height=move || format!("{}px", rect.get().3)
+                style:pointer-events=move || if editing.get() { "auto" } else { "none" }
+                style:cursor=move || if editing.get() { "crosshair" } else { "auto" }
+                on:pointerdown=pointer_down
+                on:pointermove=pointer_move
+                on:pointerup=pointer_up
+                on:pointercancel=pointer_up
+            >
+                <svg class="size-full overflow-visible">
+                    {move || {
+                        let (_, _, w, h) = rect.get();
+                        visible()
+                            .into_iter()
+                            .map(|annotation| shape_view(annotation, w, h))
+                            .collect_view()
+                    }}
+                </svg>
+            </div>
+
+            // Toolbar
+            <Show when=move || editing.get()>
+                <div
+                    class="absolute top-2 left-2 flex items-center gap-1 p-1 rounded bg-black/60 text-white text-sm pointer-events-auto"
+                    on:pointerdown=move |ev| ev.stop_propagation()
+                >
+                    {Tool::ALL
+                        .into_iter()
+                        .map(|t| {
+                            view! {
+                                <button
+                                    title=format!("{t:?}")
+                                    on:click=move |_| set_tool.set(t)
+                                    class=move || {
+                                        format!(
+                                            "w-7 h-7 rounded cursor-pointer hover:bg-white/10 {}",
+                                            if tool.get() == t { "bg-white/20" } else { "" },
+                                        )
+                                    }
+                                >
+                                    {tool_glyph(t)}
+                                </button>
+                            }
+                        })
+                        .collect_view()}
+                    <input
+                        type="color"
+                        title="Color"
+                        prop:value=color
+                        on:input=move |ev| set_color.set(event_target_value(&ev))
+                        class="w-7 h-7 bg-transparent cursor-pointer"
+                    />
+                    <button on:click=undo class="px-2 h-7 rounded cursor-pointer hover:bg-white/10">
+                        "Undo"
+                    </button>
+                    <button on:click=clear class="px-2 h-7 rounded cursor-pointer hover:bg-white/10">
+                        "Clear"
+                    </button>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+fn tool_glyph(tool: Tool) -> &'static str {
+    match tool {
+        Tool::Pen => "✎",
+        Tool::Arrow => "↗",
+        Tool::Rect => "▭",
+        Tool::Ellipse => "◯",
+        Tool::Text => "T",
+    }
+}
+
+/// SVG for `annotation` on a picture `w`×`h` pixels.
+fn shape_view(annotation: Annotation, w: f64, h: f64) -> AnyView {
+    let Annotation { color, shape, .. } = annotation;
+    let px = |p: Point| (p.x * w, p.y * h);
+    match shape {
+        Shape::Pen { points } => {
+            let points = points
+                .into_iter()
+                .map(|p| {
+                    let (x, y) = px(p);
+                    format!("{x},{y}")
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            view! {
+                <polyline
+                    points=points
+                    fill="none"
+                    stroke=color
+                    stroke-width=STROKE_WIDTH
+                    stroke-linecap="round"
+                    stroke-linejoin="round"
+                />
+            }
+            .into_any()
+        }
+        Shape::Arrow { from, to } => {
+            let ((x1, y1), (x2, y2)) = (px(from), px(to));
+            let angle = (y2 - y1).atan2(x2 - x1);
+            let len = ((x2 - x1).hypot(y2 - y1) * 0.3).min(16.0);
+            let head = |da: f64| {
+                let a = angle + std::f64::consts::PI + da;
+                format!("{},{}", x2 + len * a.cos(), y2 + len * a.sin())
+            };
+            let points = format!("{} {x2},{y2} {}", head(-0.45), head(0.45));
+            view! {
+                <g stroke=color fill="none" stroke-width=STROKE_WIDTH stroke-linecap="round">
+                    <line x1=x1 y1=y1 x2=x2 y2=y2 />
+                    <polyline points=points stroke-linejoin="round" />
+                </g>
+            }
+            .into_any()
+        }
+        Shape::Rect { from, to } => {
+            let ((x1, y1), (x2, y2)) = (px(from), px(to));
+            view! {
+                <rect
+                    x=x1.min(x2)
+                    y=y1.min(y2)
+                    width=(x2 - x1).abs()
+                    height=(y2 - y1).abs()
+                    fill="none"
+                    stroke=color
+                    stroke-width=STROKE_WIDTH
+                />
+            }
+            .into_any()
+        }
+        Shape::Ellipse { from, to } => {
+            let ((x1, y1), (x2, y2)) = (px(from), px(to));
+            view! {
+                <ellipse
+                    cx=(x1 + x2) / 2.0
+                    cy=(y1 + y2) / 2.0
+                    rx=(x2 - x1).abs() / 2.0
+                    ry=(y2 - y1).abs() / 2.0
+                    fill="none"
+                    stroke=color
+                    stroke-width=STROKE_WIDTH
+                />
+            }
+            .into_any()
+        }
+        Shape::Text { at, text } => {
+            let (x, y) = px(at);
+            view! {
+                <text
+                    x=x
+                    y=y
+                    fill=color
+                    font-size=(h * 0.05).max(12.0)
+                    font-family="sans-serif"
+                    dominant-baseline="middle"
+                >
+                    {text}
+                </text>
+            }
+            .into_any()
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::hooks::Meter;
+use crate::loudness::ChannelLevels;
+use leptos::prelude::*;
+
+/// Bottom of the meter scale in dBFS.
+const FLOOR_DB: f64 = -60.0;
+
+/// Position of `db` along the meter, as a CSS percentage.
+fn percent(db: f64) -> String {
+    format!("{}%", ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0) * 100.0)
+}
+
+fn color(db: f64) -> &'static str {
+    if db > -6.0 {
+        "bg-red-500"
+    } else if db > -18.0 {
+        "bg-yellow-400"
+    } else {
+        "bg-green-500"
+    }
+}
+
+fn format_db(db: f64) -> String {
+    if db.is_finite() {
+        format!("{db:.1}")
+    } else {
+        "-inf".to_string()
+    }
+}
+
+/// A bar per channel, RMS filled with a tick at the falling peak, and the short-term
+/// loudness beside them.
+#[component]
+pub fn AudioMeters(#[prop(into)] meter: Signal<Meter>) -> impl IntoView {
+    let channels = Memo::new(move |_| meter.with(|meter| meter.channels.len()));
+    let level = move |i: usize| {
+        meter.with(|meter| {
+            meter
+                .channels
+                .get(i)
+                .copied()
+                .unwrap_or(ChannelLevels::SILENT)
+        })
+    };
+
+    view! {
+        <div
+            class="flex items-center space-x-2"
+            title=move || {
+                meter
+                    .with(|meter| {
+                        meter
+                            .channels
+                            .iter()
+                            .enumerate()
+                            .map(|(i, levels)| {
+                                format!(
+                                    "Ch {}: peak {} dBFS, RMS {} dBFS",
+                                    i + 1,
+                                    format_db(levels.peak),
+                                    format_db(levels.rms),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+            }
+        >
+            <div class="flex flex-col justify-center gap-px w-24">
+                {move || {
+                    (0..channels.get())
+                        .map(|i| {
+                            view! {
+                                <div class="relative h-1 bg-gray-700 overflow-hidden">
+                                    <div
+                                        class=move || format!("absolute inset-y-0 left-0 {}", color(level(i).rms))
+                                        style:width=move || percent(level(i).rms)
+                                    />
+                                    <div
+                                        class=move || format!("absolute inset-y-0 w-0.5 {}", color(level(i).peak))
+                                        style:left=move || percent(level(i).peak)
+                                    />
+                                </div>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </div>
+            <span class="w-20 text-xs font-mono text-gray-300 text-right">
+                {move || match meter.with(|meter| meter.short_term) {
+                    Some(lufs) if lufs.is_finite() => format!("{lufs:.1} LUFS"),
+                    _ => "-- LUFS".to_string(),
+                }}
+            </span>
+        </div>
+    }
+}
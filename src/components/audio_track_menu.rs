@@ -0,0 +1,84 @@
+use super::icon::AudioTracks;
+use crate::audio::{embedded_index, AudioSource};
+use leptos::prelude::*;
+use leptos::*;
+use leptos_use::on_click_outside;
+
+/// Audio button with a pop-up list of `tracks`.
+///
+/// Tracks of the media other than its first are greyed out where the browser can't switch
+/// between them.
+#[component]
+pub fn AudioTrackMenu(
+    #[prop(into)] tracks: Signal<Vec<AudioSource>>,
+    /// Index into `tracks` of the track heard.
+    #[prop(into)]
+    selected: Signal<usize>,
+    /// Whether the browser supports `audioTracks`.
+    #[prop(into)]
+    can_switch_embedded: Signal<bool>,
+    #[prop(into)] on_change: Callback<usize>,
+) -> impl IntoView {
+    let menu_ref = NodeRef::<html::Div>::new();
+    let (is_open, set_is_open) = signal(false);
+
+    let _ = on_click_outside(menu_ref, move |_| set_is_open.set(false));
+
+    view! {
+        <div node_ref=menu_ref class="relative">
+            <button
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    set_is_open.update(|open| *open = !*open);
+                }
+                on:keydown=move |ev| ev.prevent_default()
+                title="Audio track"
+                class="text-white hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer"
+            >
+                <AudioTracks />
+            </button>
+            <Show when=move || is_open.get()>
+                <ul class="absolute bottom-full right-0 mb-2 py-1 rounded bg-gray-800 shadow-lg text-sm">
+                    {move || {
+                        let tracks = tracks.get();
+                        tracks
+                            .iter()
+                            .enumerate()
+                            .map(|(i, track)| {
+                                let available = can_switch_embedded.get()
+                                    || embedded_index(&tracks, i).is_none_or(|embedded| embedded == 0);
+                                view! {
+                                    <li
+                                        on:click=move |ev| {
+                                            ev.stop_propagation();
+                                            if available {
+                                                on_change.run(i);
+                                                set_is_open.set(false);
+                                            }
+                                        }
+                                        title=(!available).then_some("Not supported by this browser")
+                                        class=move || {
+                                            format!(
+                                                "px-4 py-1 whitespace-nowrap {}",
+                                                if !available {
+                                                    "text-gray-500 cursor-not-allowed"
+                                                } else if selected.get() == i {
+                                                    "text-blue-400 cursor-pointer hover:bg-white/10"
+                                                } else {
+                                                    "text-white cursor-pointer hover:bg-white/10"
+                                                },
+                                            )
+                                        }
+                                    >
+                                        {track.label.clone()}
+                                        <span class="ml-2 text-gray-400">{track.language.clone()}</span>
+                                    </li>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </ul>
+            </Show>
+        </div>
+    }
+}
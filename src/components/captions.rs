@@ -0,0 +1,158 @@
+use crate::captions::{self, TextTrack, TrackKind};
+use crate::fetch::fetch_text;
+use crate::frame_rate::FrameRate;
+use crate::timecode::timecode;
+use leptos::prelude::*;
+use leptos::*;
+use leptos_use::on_click_outside;
+
+/// CC button with a pop-up list of `tracks` and an "Off" entry.
+#[component]
+pub fn CaptionsMenu(
+    #[prop(into)] tracks: Signal<Vec<TextTrack>>,
+    /// Index into `tracks` of the captions shown.
+    #[prop(into)]
+    selected: Signal<Option<usize>>,
+    #[prop(into)] on_change: Callback<Option<usize>>,
+) -> impl IntoView {
+    let menu_ref = NodeRef::<html::Div>::new();
+    let (is_open, set_is_open) = signal(false);
+
+    let _ = on_click_outside(menu_ref, move |_| set_is_open.set(false));
+
+    let item = move |index: Option<usize>, label: String, detail: String| {
+        view! {
+            <li
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    on_change.run(index);
+                    set_is_open.set(false);
+                }
+                class=move || {
+                    format!(
+                        "px-4 py-1 whitespace-nowrap cursor-pointer hover:bg-white/10 {}",
+                        if selected.get() == index { "text-blue-400" } else { "text-white" },
+                    )
+                }
+            >
+                {label}
+                <span class="ml-2 text-gray-400">{detail}</span>
+            </li>
+        }
+    };
+
+    view! {
+        <div node_ref=menu_ref class="relative">
+            <button
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    set_is_open.update(|open| *open = !*open);
+                }
+                on:keydown=move |ev| ev.prevent_default()
+                title="Subtitles"
+                class=move || {
+                    format!(
+                        "text-sm font-bold hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer {}",
+                        if selected.get().is_some() { "text-white" } else { "text-gray-400" },
+                    )
+                }
+            >
+                "CC"
+            </button>
+            <Show when=move || is_open.get()>
+                <ul class="absolute bottom-full right-0 mb-2 py-1 rounded bg-gray-800 shadow-lg text-sm">
+                    {item(None, "Off".to_string(), String::new())}
+                    {move || {
+                        tracks
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, track)| {
+                                let detail = match track.kind {
+                                    TrackKind::Subtitles => track.language,
+                                    TrackKind::Captions => format!("{} CC", track.language),
+                                };
+                                item(Some(i), track.label, detail.trim().to_string())
+                            })
+                            .collect_view()
+                    }}
+                </ul>
+            </Show>
+        </div>
+    }
+}
+
+/// The cues of `track` showing at `frame`, drawn over the bottom of the picture.
+///
+/// Cues are snapped to frames, so they change on the same frame the timecode does. While
+/// paused each cue is labelled with the timecodes of its first and last frames.
+#[component]
+pub fn CaptionsOverlay(
+    #[prop(into)] track: Signal<Option<TextTrack>>,
+    #[prop(into)] frame: Signal<i32>,
+    #[prop(into)] fps: Signal<FrameRate>,
+    #[prop(into)] start: Signal<i32>,
+    #[prop(into)] is_playing: Signal<bool>,
+    /// Raise the cues clear of controls drawn over the picture.
+    #[prop(into)]
+    lifted: Signal<bool>,
+) -> impl IntoView {
+    let cues = LocalResource::new(move || {
+        let src = track.with(|track| track.as_ref().map(|track| track.src.clone()));
+        async move {
+            let src = src?;
+            let text = fetch_text(&src).await.ok()?;
+            captions::parse(&text, &src).ok()
+        }
+    });
+    let captions = Memo::new(move |_| {
+        cues.with(|cues| match cues {
+            Some(Some(cues)) => captions::to_captions(cues, fps.get()),
+            _ => Vec::new(),
+        })
+    });
+    // Only touch the DOM when the cues on screen change, not on every frame.
+    let active = Memo::new(move |_| {
+        let frame = frame.get();
+        captions.with(|captions| {
+            captions::active(captions, frame)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+
+    view! {
+        <div
+            class="absolute inset-x-0 flex flex-col items-center gap-1 px-8 pointer-events-none transition-[bottom] duration-200"
+            style:bottom=move || if lifted.get() { "6rem" } else { "1.5rem" }
+        >
+            {move || {
+                active
+                    .get()
+                    .into_iter()
+                    .map(|caption| {
+                        let timing = move || {
+                            (!is_playing.get())
+                                .then(|| {
+                                    let (fps, start) = (fps.get(), start.get());
+                                    format!(
+                                        "{} – {}",
+                                        timecode(caption.frame + start, fps),
+                                        timecode(caption.end_frame + start, fps),
+                                    )
+                                })
+                        };
+                        view! {
+                            <div class="flex flex-col items-center">
+                                <div class="px-2 py-0.5 rounded bg-black/75 text-white text-center whitespace-pre-line text-lg group-fullscreen:text-3xl">
+                                    {caption.text}
+                                </div>
+                                <div class="text-xs font-mono text-gray-300">{timing}</div>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,129 @@
+use super::video_controller::VideoController;
+use crate::comments::NewComment;
+use crate::hooks::Comments;
+use crate::timecode::timecode;
+use leptos::prelude::*;
+use leptos::*;
+
+/// Review comments in timecode order, with a box for adding one at the playhead.
+///
+/// Clicking a comment's timecode seeks the player to it. While in/out marks are set, new
+/// comments cover the marked range instead of the current frame.
+#[component]
+pub fn CommentsPanel(
+    comments: Comments,
+    controller: VideoController,
+    /// Name new comments are signed with.
+    #[prop(into, optional)]
+    author: MaybeProp<String>,
+) -> impl IntoView {
+    let input_ref = NodeRef::<html::Textarea>::new();
+    let fps = controller.fps();
+    let start = controller.start();
+    let range = controller.range();
+    let has_marks =
+        move || controller.mark_in().get().is_some() || controller.mark_out().get().is_some();
+
+    let format = move |frame: i32| timecode(frame + start.get(), fps.get());
+
+    // Range or frame a new comment would be pinned to.
+    let target = move || {
+        if has_marks() {
+            let (first, last) = range.get();
+            (first, Some(last))
+        } else {
+            (controller.frame().get(), None)
+        }
+    };
+
+    let submit = move || {
+        if let Some(input) = input_ref.get() {
+            let text = input.value();
+            if text.trim().is_empty() {
+                return;
+            }
+            let (frame, end_frame) = target();
+            comments.add(NewComment {
+                frame,
+                end_frame,
+                author: author.get().unwrap_or_default(),
+                text: text.trim().to_string(),
+            });
+            input.set_value("");
+        }
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2 bg-gray-900 text-white text-sm">
+            <div class="flex flex-col gap-1">
+                <textarea
+                    node_ref=input_ref
+                    rows="2"
+                    placeholder="Comment"
+                    class="w-full p-1 rounded bg-gray-800 outline-none focus:ring-1 focus:ring-blue-500 resize-none"
+                    on:keydown=move |ev| {
+                        // Keep typed keys away from the player shortcuts.
+                        ev.stop_propagation();
+                        if ev.key() == "Enter" && (ev.ctrl_key() || ev.meta_key()) {
+                            ev.prevent_default();
+                            submit();
+                        }
+                    }
+                />
+                <button
+                    on:click=move |_| submit()
+                    class="self-end px-2 py-1 rounded bg-blue-600 hover:bg-blue-500 font-mono cursor-pointer"
+                >
+                    {move || match target() {
+                        (frame, Some(last)) => format!("Add at {}–{}", format(frame), format(last)),
+                        (frame, None) => format!("Add at {}", format(frame)),
+                    }}
+                </button>
+                {move || {
+                    comments
+                        .error()
+                        .get()
+                        .map(|err| view! { <p class="text-red-400">{err.to_string()}</p> })
+                }}
+            </div>
+
+            <ul class="flex flex-col divide-y divide-white/10 overflow-y-auto">
+                <For each=move || comments.list().get() key=|comment| comment.id let:comment>
+                    <li class=move || {
+                        let (frame, end_frame) = (comment.frame, comment.end_frame);
+                        let current = controller.frame().get();
+                        format!(
+                            "flex items-start gap-2 py-1 {}",
+                            if current >= frame && current <= end_frame.unwrap_or(frame) {
+                                "bg-white/10"
+                            } else {
+                                ""
+                            },
+                        )
+                    }>
+                        <button
+                            on:click=move |_| controller.seek(comment.frame)
+                            class="font-mono text-blue-400 hover:underline cursor-pointer whitespace-nowrap"
+                        >
+                            {move || format(comment.frame)}
+                            {comment
+                                .end_frame
+                                .map(|last| move || format!("–{}", format(last)))}
+                        </button>
+                        <div class="flex-auto">
+                            <span class="text-gray-400">{comment.author.clone()}</span>
+                            <p class="whitespace-pre-wrap">{comment.text.clone()}</p>
+                        </div>
+                        <button
+                            on:click=move |_| comments.remove(comment.id)
+                            title="Delete"
+                            class="text-gray-500 hover:text-red-400 px-1 cursor-pointer"
+                        >
+                            "×"
+                        </button>
+                    </li>
+                </For>
+            </ul>
+        </div>
+    }
+}
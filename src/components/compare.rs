@@ -0,0 +1,211 @@
+use super::icon::Compare;
+use crate::annotation::picture_rect;
+use crate::compare::{CompareMode, WipeDirection};
+use crate::hooks::use_video_frame_callback;
+use leptos::ev::PointerEvent;
+use leptos::prelude::*;
+use leptos::wasm_bindgen::JsCast;
+use leptos::*;
+use leptos_use::{on_click_outside, use_event_listener};
+use web_sys::CanvasRenderingContext2d;
+
+/// Compare button with a pop-up list of [`CompareMode`]s, and Off.
+#[component]
+pub fn CompareMenu(
+    #[prop(into)] mode: Signal<Option<CompareMode>>,
+    #[prop(into)] on_change: Callback<Option<CompareMode>>,
+) -> impl IntoView {
+    let menu_ref = NodeRef::<html::Div>::new();
+    let (is_open, set_is_open) = signal(false);
+
+    let _ = on_click_outside(menu_ref, move |_| set_is_open.set(false));
+
+    view! {
+        <div node_ref=menu_ref class="relative">
+            <button
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    set_is_open.update(|open| *open = !*open);
+                }
+                on:keydown=move |ev| ev.prevent_default()
+                title="Compare versions (X swaps A and B)"
+                class=move || {
+                    format!(
+                        "hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer {}",
+                        if mode.get().is_some() { "text-blue-400" } else { "text-white" },
+                    )
+                }
+            >
+                <Compare />
+            </button>
+            <Show when=move || is_open.get()>
+                <ul class="absolute bottom-full right-0 mb-2 py-1 rounded bg-gray-800 shadow-lg text-sm">
+                    {std::iter::once((None, "Off"))
+                        .chain(CompareMode::ALL.map(|mode| (Some(mode), mode.label())))
+                        .map(|(option, label)| {
+                            view! {
+                                <li
+                                    on:click=move |ev| {
+                                        ev.stop_propagation();
+                                        on_change.run(option);
+                                        set_is_open.set(false);
+                                    }
+                                    class=move || {
+                                        format!(
+                                            "px-4 py-1 whitespace-nowrap cursor-pointer hover:bg-white/10 {}",
+                                            if mode.get() == option {
+                                                "text-blue-400"
+                                            } else {
+                                                "text-white"
+                                            },
+                                        )
+                                    }
+                                >
+                                    {label}
+                                </li>
+                            }
+                        })
+                        .collect_view()}
+                </ul>
+            </Show>
+        </div>
+    }
+}
+
+/// The line splitting a wipe, draggable across the picture box.
+#[component]
+pub fn WipeHandle(
+    #[prop(into)] direction: Signal<WipeDirection>,
+    /// Fraction of the way across, or down, the box.
+    #[prop(into)]
+    position: Signal<f64>,
+    #[prop(into)] on_change: Callback<f64>,
+) -> impl IntoView {
+    let box_ref = NodeRef::<html::Div>::new();
+    let handle_ref = NodeRef::<html::Div>::new();
+    let (is_dragging, set_is_dragging) = signal(false);
+
+    let move_to = move |ev: &PointerEvent| {
+        if let Some(el) = box_ref.get() {
+            let r = el.get_bounding_client_rect();
+            let fraction = match direction.get_untracked() {
+                WipeDirection::Vertical => (ev.client_x() as f64 - r.left()) / r.width(),
+                WipeDirection::Horizontal => (ev.client_y() as f64 - r.top()) / r.height(),
+            };
+            on_change.run(fraction.clamp(0.0, 1.0));
+        }
+    };
+
+    let pointer_down = move |ev: PointerEvent| {
+        ev.stop_propagation();
+        if let Some(handle) = handle_ref.get() {
+            let _ = handle.set_pointer_capture(ev.pointer_id());
+        }
+        set_is_dragging.set(true);
+        move_to(&ev);
+    };
+
+    view! {
+        <div node_ref=box_ref class="absolute inset-0 pointer-events-none">
+            <div
+                node_ref=handle_ref
+                class=move || {
+                    format!(
+                        "absolute flex items-center justify-center pointer-events-auto touch-none {}",
+                        match direction.get() {
+                            WipeDirection::Vertical => {
+                                "inset-y-0 w-4 -translate-x-1/2 cursor-ew-resize"
+                            }
+                            WipeDirection::Horizontal => {
+                                "inset-x-0 h-4 -translate-y-1/2 cursor-ns-resize"
+                            }
+                        },
+                    )
+                }
+                style:left=move || {
+                    (direction.get() == WipeDirection::Vertical)
+                        .then(|| format!("{}%", 100.0 * position.get()))
+                }
+                style:top=move || {
+                    (direction.get() == WipeDirection::Horizontal)
+                        .then(|| format!("{}%", 100.0 * position.get()))
+                }
+                on:pointerdown=pointer_down
+                on:pointermove=move |ev| {
+                    if is_dragging.get_untracked() {
+                        move_to(&ev);
+                    }
+                }
+                on:pointerup=move |_| set_is_dragging.set(false)
+                on:pointercancel=move |_| set_is_dragging.set(false)
+                on:click=move |ev| ev.stop_propagation()
+            >
+                <div class=move || {
+                    match direction.get() {
+                        WipeDirection::Vertical => "h-full w-0.5 bg-white/80",
+                        WipeDirection::Horizontal => "w-full h-0.5 bg-white/80",
+                    }
+                } />
+                <div class="absolute size-3 rounded-full bg-white shadow" />
+            </div>
+        </div>
+    }
+}
+
+/// `a` with `b` blended over it in `difference` mode, redrawn as either presents a new
+/// frame. Both are scaled to `a`'s picture, so versions at different resolutions still
+/// line up.
+///
+/// Drawing cross-origin media needs no CORS, as the canvas is never read back.
+#[component]
+pub fn DifferenceCanvas(a: NodeRef<html::Video>, b: NodeRef<html::Video>) -> impl IntoView {
+    let canvas_ref = NodeRef::<html::Canvas>::new();
+
+    let draw = move || {
+        let (Some(canvas), Some(a), Some(b)) = (
+            canvas_ref.get_untracked(),
+            a.get_untracked(),
+            b.get_untracked(),
+        ) else {
+            return;
+        };
+        let (width, height) = (canvas.client_width() as u32, canvas.client_height() as u32);
+        if canvas.width() != width || canvas.height() != height {
+            canvas.set_width(width);
+            canvas.set_height(height);
+        }
+        let Some(context) = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        else {
+            return;
+        };
+        let (x, y, w, h) = picture_rect(
+            width as f64,
+            height as f64,
+            a.video_width() as f64,
+            a.video_height() as f64,
+        );
+        let _ = context.set_global_composite_operation("source-over");
+        context.set_fill_style_str("black");
+        context.fill_rect(0.0, 0.0, width as f64, height as f64);
+        let _ = context.draw_image_with_html_video_element_and_dw_and_dh(&a, x, y, w, h);
+        let _ = context.set_global_composite_operation("difference");
+        let _ = context.draw_image_with_html_video_element_and_dw_and_dh(&b, x, y, w, h);
+    };
+
+    let _ = use_video_frame_callback(a, move |_| draw());
+    let _ = use_video_frame_callback(b, move |_| draw());
+    let _ = use_event_listener(a, ev::seeked, move |_| draw());
+    let _ = use_event_listener(b, ev::seeked, move |_| draw());
+    let _ = use_event_listener(window(), ev::resize, move |_| draw());
+    Effect::new(move |_| {
+        if canvas_ref.get().is_some() {
+            draw();
+        }
+    });
+
+    view! { <canvas node_ref=canvas_ref class="absolute size-full pointer-events-none" /> }
+}
@@ -1,3 +1,4 @@
+use super::video_controller::LoopMode;
 use leptos::either::*;
 use leptos::prelude::*;
 
@@ -201,3 +202,149 @@ pub fn Fullscreen(#[prop(into)] fullscreen: Signal<bool>) -> impl IntoView {
         }
     }
 }
+
+#[component]
+pub fn LoopOff() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M4 12v-3c0 -1.336 .873 -2.468 2.08 -2.856m3.92 -.144h10m-3 -3l3 3l-3 3"></path>
+            <path d="M20 12v3a3 3 0 0 1 -.133 .886m-1.99 1.984a3 3 0 0 1 -.877 .13h-13m3 3l-3 -3l3 -3"></path>
+            <path d="M3 3l18 18"></path>
+        </svg>
+    }
+}
+
+#[component]
+pub fn LoopOn() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M4 12v-3a3 3 0 0 1 3 -3h13m-3 -3l3 3l-3 3"></path>
+            <path d="M20 12v3a3 3 0 0 1 -3 3h-13m3 3l-3 -3l3 -3"></path>
+        </svg>
+    }
+}
+
+#[component]
+pub fn LoopPingPong() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M21 17l-18 0"></path>
+            <path d="M6 10l-3 -3l3 -3"></path>
+            <path d="M3 7l18 0"></path>
+            <path d="M18 20l3 -3l-3 -3"></path>
+        </svg>
+    }
+}
+
+#[component]
+pub fn Loop(#[prop(into)] mode: Signal<LoopMode>) -> impl IntoView {
+    move || match mode.get() {
+        LoopMode::Off => EitherOf3::A(LoopOff()),
+        LoopMode::Loop => EitherOf3::B(LoopOn()),
+        LoopMode::PingPong => EitherOf3::C(LoopPingPong()),
+    }
+}
+
+#[component]
+pub fn Draw() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M4 20h4l10.5 -10.5a2.828 2.828 0 1 0 -4 -4l-10.5 10.5v4"></path>
+            <path d="M13.5 6.5l4 4"></path>
+        </svg>
+    }
+}
+
+#[component]
+pub fn AudioTracks() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M4 15a2 2 0 0 1 2 -2h1a2 2 0 0 1 2 2v3a2 2 0 0 1 -2 2h-1a2 2 0 0 1 -2 -2l0 -3"></path>
+            <path d="M15 15a2 2 0 0 1 2 -2h1a2 2 0 0 1 2 2v3a2 2 0 0 1 -2 2h-1a2 2 0 0 1 -2 -2l0 -3"></path>
+            <path d="M4 15v-3a8 8 0 0 1 16 0v3"></path>
+        </svg>
+    }
+}
+
+#[component]
+pub fn Compare() -> impl IntoView {
+    view! {
+        <svg
+            class="group-hover:text-emphasis group-hover:dark:text-emphasis-dark transition-colors delay-75 duration-200 ease-in-out"
+            width="20"
+            height="20"
+            viewBox="0 0 24 24"
+            stroke-linecap="round"
+            stroke-linejoin="round"
+            stroke-width="2"
+            stroke="currentColor"
+            fill="none"
+            role="graphics-symbol"
+        >
+            <path stroke="none" d="M0 0h24v24H0z" fill="none"></path>
+            <path d="M4 6a2 2 0 0 1 2 -2h12a2 2 0 0 1 2 2v12a2 2 0 0 1 -2 2h-12a2 2 0 0 1 -2 -2l0 -12"></path>
+            <path d="M12 4v16"></path>
+            <path d="M12 4h6a2 2 0 0 1 2 2v12a2 2 0 0 1 -2 2h-6z" fill="currentColor" stroke="none"></path>
+        </svg>
+    }
+}
@@ -1,2 +1,13 @@
+pub mod annotation_layer;
+pub mod audio_meter;
+pub mod audio_track_menu;
+pub mod captions;
+pub mod comments_panel;
+pub mod compare;
 pub mod icon;
+pub mod rate_menu;
+pub mod scrub_preview;
+pub mod timecode_input;
 pub mod video;
+pub mod video_controller;
+pub mod waveform;
@@ -0,0 +1,63 @@
+use super::video_controller::{format_rate, MENU_RATES};
+use leptos::prelude::*;
+use leptos::*;
+use leptos_use::on_click_outside;
+
+/// Playback speed button with a pop-up list of [`MENU_RATES`].
+///
+/// The button shows the current rate, including shuttle rates outside the menu.
+#[component]
+pub fn RateMenu(
+    #[prop(into)] rate: Signal<f64>,
+    #[prop(into)] on_change: Callback<f64>,
+) -> impl IntoView {
+    let menu_ref = NodeRef::<html::Div>::new();
+    let (is_open, set_is_open) = signal(false);
+
+    let _ = on_click_outside(menu_ref, move |_| set_is_open.set(false));
+
+    view! {
+        <div node_ref=menu_ref class="relative">
+            <button
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    set_is_open.update(|open| *open = !*open);
+                }
+                on:keydown=move |ev| ev.prevent_default()
+                class="w-14 text-white text-sm font-mono hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer"
+            >
+                {move || format_rate(rate.get())}
+            </button>
+            <Show when=move || is_open.get()>
+                <ul class="absolute bottom-full right-0 mb-2 py-1 rounded bg-gray-800 shadow-lg text-sm font-mono">
+                    {MENU_RATES
+                        .into_iter()
+                        .map(|option| {
+                            view! {
+                                <li
+                                    on:click=move |ev| {
+                                        ev.stop_propagation();
+                                        on_change.run(option);
+                                        set_is_open.set(false);
+                                    }
+                                    class=move || {
+                                        format!(
+                                            "px-4 py-1 cursor-pointer hover:bg-white/10 {}",
+                                            if rate.get() == option {
+                                                "text-blue-400"
+                                            } else {
+                                                "text-white"
+                                            },
+                                        )
+                                    }
+                                >
+                                    {format_rate(option)}
+                                </li>
+                            }
+                        })
+                        .collect_view()}
+                </ul>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,157 @@
+use crate::fetch::fetch_text;
+use crate::frame_rate::FrameRate;
+use crate::marker::Marker;
+use crate::thumbnail::{self, parse_track, Thumbnail};
+use crate::timecode::timecode;
+use crate::timeline::TimelineView;
+use leptos::prelude::*;
+use leptos::*;
+
+/// Width of the preview box in pixels; half of it keeps the box inside the bar.
+const PREVIEW_WIDTH: f64 = 160.0;
+
+/// Floating preview above the progress bar for the frame under the pointer.
+///
+/// Shows the frame's thumbnail, its timecode and the marker under the pointer, if any. The
+/// thumbnail comes from the WebVTT sprite `track` once it has loaded, otherwise from seeking a
+/// muted `<video>` of `decoder_src`, ideally a low-res proxy. With neither, only the
+/// timecode is shown.
+#[component]
+pub fn ScrubPreview(
+    /// Frame under the pointer; `None` hides the preview.
+    #[prop(into)]
+    frame: Signal<Option<i32>>,
+    /// Frames the progress bar spans.
+    #[prop(into)]
+    timeline: Signal<TimelineView>,
+    #[prop(into)] fps: Signal<FrameRate>,
+    #[prop(into)] start: Signal<i32>,
+    /// URL of a WebVTT thumbnail track.
+    #[prop(into)]
+    track: MaybeProp<String>,
+    /// Media to decode previews from when there's no track; empty for none.
+    #[prop(into)]
+    decoder_src: Signal<String>,
+    #[prop(into)] marker: Signal<Option<Marker>>,
+) -> impl IntoView {
+    let decoder_ref = NodeRef::<html::Video>::new();
+    let last_frame = StoredValue::new(0);
+
+    let thumbnails = LocalResource::new(move || {
+        let url = track.get();
+        async move {
+            let url = url?;
+            let text = fetch_text(&url).await.ok()?;
+            parse_track(&text, &url).ok()
+        }
+    });
+    // Fall back to the decoder while the track loads or if it fails to.
+    let has_track = move || thumbnails.with(|list| matches!(list, Some(Some(_))));
+
+    // Seek the decoder one request at a time, keeping only the newest request queued, so a
+    // fast sweep over the bar doesn't pile up seeks.
+    let is_seeking = StoredValue::new(false);
+    let queued = StoredValue::new(None::<i32>);
+    let seek_decoder = move |frame: i32| {
+        if is_seeking.get_value() {
+            queued.set_value(Some(frame));
+            return;
+        }
+        if let Some(video) = decoder_ref.get() {
+            is_seeking.set_value(true);
+            video.set_current_time(fps.get_untracked().seek_time(frame));
+        }
+    };
+    Effect::new(move |_| {
+        if let Some(frame) = frame.get() {
+            last_frame.set_value(frame);
+            if !has_track() {
+                seek_decoder(frame);
+            }
+        }
+    });
+
+    let sprite = move || {
+        let time = fps.get().time_of(frame.get()?);
+        thumbnails
+            .get()
+            .flatten()
+            .and_then(|list| thumbnail::find(&list, time).cloned())
+    };
+
+    view! {
+        <div
+            class="absolute bottom-3 -translate-x-1/2 p-1 rounded bg-black/80 text-white text-xs pointer-events-none flex flex-col items-center gap-1"
+            style:visibility=move || if frame.get().is_some() { "visible" } else { "hidden" }
+            style:left=move || {
+                let frame = frame.get().unwrap_or_else(|| last_frame.get_value());
+                format!(
+                    "clamp({half}px, {}%, calc(100% - {half}px))",
+                    100.0 * timeline.get().fraction(frame as f64),
+                    half = PREVIEW_WIDTH / 2.0 + 4.0,
+                )
+            }
+        >
+            {move || sprite().map(|thumbnail| thumbnail_view(thumbnail, PREVIEW_WIDTH))}
+            <Show when=move || !has_track() && !decoder_src.get().is_empty()>
+                <video
+                    node_ref=decoder_ref
+                    src=decoder_src
+                    muted
+                    playsinline
+                    preload="auto"
+                    disablepictureinpicture
+                    style:width=format!("{PREVIEW_WIDTH}px")
+                    class="object-contain bg-black"
+                    on:seeked=move |_| {
+                        is_seeking.set_value(false);
+                        if let Some(frame) = queued.try_update_value(Option::take).flatten() {
+                            seek_decoder(frame);
+                        }
+                    }
+                />
+            </Show>
+            <span class="font-mono">
+                {move || frame.get().map(|frame| timecode(frame + start.get(), fps.get()))}
+            </span>
+            {move || {
+                marker
+                    .get()
+                    .map(|marker| {
+                        view! {
+                            <span class="whitespace-nowrap">
+                                <span style:color=marker.color().to_string()>"■ "</span>
+                                {marker.label.clone()}
+                                {marker
+                                    .category
+                                    .clone()
+                                    .map(|category| {
+                                        view! { <span class="ml-1 text-gray-400">{category}</span> }
+                                    })}
+                            </span>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
+
+/// A thumbnail at most `width` pixels wide: a sprite-sheet region, or the whole image.
+fn thumbnail_view(thumbnail: Thumbnail, width: f64) -> AnyView {
+    match thumbnail.region {
+        Some((x, y, w, h)) => view! {
+            <div
+                style:width=format!("{w}px")
+                style:height=format!("{h}px")
+                style:max-width=format!("{width}px")
+                style:background-image=format!("url(\"{}\")", thumbnail.url)
+                style:background-position=format!("-{x}px -{y}px")
+            />
+        }
+        .into_any(),
+        None => view! {
+            <img src=thumbnail.url style:width=format!("{width}px") class="object-contain" />
+        }
+        .into_any(),
+    }
+}
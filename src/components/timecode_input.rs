@@ -0,0 +1,97 @@
+use crate::frame_rate::FrameRate;
+use crate::timecode::{parse_jump, timecode, TimecodeError};
+use leptos::prelude::*;
+use leptos::*;
+
+/// Timecode readout that turns into a jump field on focus.
+///
+/// Enter resolves the text with [`parse_jump`] and hands the frame to `on_seek`;
+/// Escape or blur restores the live timecode. `frame` is zero-based media frame; `start`
+/// is the source frame it is displayed relative to.
+#[component]
+pub fn TimecodeInput(
+    #[prop(into)] frame: Signal<i32>,
+    #[prop(into)] fps: Signal<FrameRate>,
+    #[prop(into, optional)] start: Signal<i32>,
+    #[prop(into)] on_seek: Callback<i32>,
+) -> impl IntoView {
+    let input_ref = NodeRef::<html::Input>::new();
+    let (is_editing, set_is_editing) = signal(false);
+    let (error, set_error) = signal(None::<TimecodeError>);
+
+    // Keep the field in sync with playback unless the user is typing into it.
+    Effect::new(move |_| {
+        let text = timecode(frame.get() + start.get(), fps.get());
+        if !is_editing.get() {
+            if let Some(input) = input_ref.get() {
+                input.set_value(&text);
+            }
+        }
+    });
+
+    let commit = move || {
+        if let Some(input) = input_ref.get() {
+            match parse_jump(
+                &input.value(),
+                frame.get_untracked(),
+                start.get_untracked(),
+                fps.get_untracked(),
+            ) {
+                Ok(target) => {
+                    set_error.set(None);
+                    on_seek.run(target);
+                    let _ = input.blur();
+                }
+                Err(err) => set_error.set(Some(err)),
+            }
+        }
+    };
+
+    let handle_keydown = move |ev: leptos::ev::KeyboardEvent| {
+        // Keep typed keys away from the player shortcuts.
+        ev.stop_propagation();
+        match ev.key().as_str() {
+            "Enter" => {
+                ev.prevent_default();
+                commit();
+            }
+            "Escape" => {
+                if let Some(input) = input_ref.get() {
+                    let _ = input.blur();
+                }
+            }
+            _ => set_error.set(None),
+        }
+    };
+
+    view! {
+        <input
+            node_ref=input_ref
+            type="text"
+            spellcheck="false"
+            autocomplete="off"
+            class=move || {
+                format!(
+                    "w-32 px-1 rounded bg-transparent text-center font-mono text-sm outline-none focus:bg-white/10 {}",
+                    if error.get().is_some() {
+                        "text-red-400 ring-1 ring-red-500"
+                    } else {
+                        "text-white"
+                    },
+                )
+            }
+            title=move || error.get().map(|err| err.to_string())
+            on:focus=move |_| {
+                set_is_editing.set(true);
+                if let Some(input) = input_ref.get() {
+                    input.select();
+                }
+            }
+            on:blur=move |_| {
+                set_error.set(None);
+                set_is_editing.set(false);
+            }
+            on:keydown=handle_keydown
+        />
+    }
+}
@@ -1,16 +1,100 @@
+use super::annotation_layer::AnnotationLayer;
+use super::audio_meter::AudioMeters;
+use super::audio_track_menu::AudioTrackMenu;
+use super::captions::{CaptionsMenu, CaptionsOverlay};
+use super::compare::{CompareMenu, DifferenceCanvas, WipeHandle};
 use super::icon::*;
+use super::rate_menu::RateMenu;
+use super::scrub_preview::ScrubPreview;
+use super::timecode_input::TimecodeInput;
+use super::video_controller::{
+    format_rate, FpsSource, LoopMode, ProxyPolicy, VideoController, JOG_RATE,
+};
+use super::waveform::Waveform;
+use crate::annotation::Annotation;
+use crate::audio::{self, AudioSource};
+use crate::captions::{default_track, TextTrack};
+use crate::compare::{self, CompareMode, Side, WipeDirection};
+use crate::frame_rate::FrameRate;
+use crate::hooks::{use_audio_meter, use_fps_detection, use_video_frame_callback, Comments};
+use crate::marker::{next_marker, prev_marker, snap, Marker};
+use crate::timecode::Timecode;
+use crate::timeline::TimelineView;
 use leptos::ev::Event;
-use leptos::logging::log;
 use leptos::prelude::*;
-use leptos::reactive::owner::StoredValue;
 use leptos::*;
 use leptos_use::core::Position;
 use leptos_use::{
-    use_debounce_fn, use_draggable_with_options, use_mouse_in_element, UseDraggableOptions,
+    use_debounce_fn, use_draggable_with_options, use_event_listener_with_options,
+    use_mouse_in_element, UseDraggableOptions, UseEventListenerOptions,
 };
 use web_sys;
 use web_sys::MouseEvent;
 
+/// How close, in pixels, a scrub has to come to a marker to snap to it.
+const MARKER_SNAP_PX: f64 = 6.0;
+
+/// Wheel travel, in pixels, that doubles or halves the timeline zoom.
+const WHEEL_ZOOM_PX: f64 = 200.0;
+
+/// How far, in frames, a playing proxy may drift from full-res before it's resynced.
+const PROXY_DRIFT_FRAMES: f64 = 3.0;
+
+/// How far, in frames, a playing compare version may drift from the picture before it's
+/// resynced. Paused, the two always show the same frame.
+const COMPARE_DRIFT_FRAMES: f64 = 1.0;
+
+/// How far, in seconds, a separate audio file may drift from the picture before it's
+/// resynced. Each resync is audible, so this is looser than lip sync strictly needs.
+const AUDIO_DRIFT: f64 = 0.1;
+
+/// The `fps` prop of [`Video`]: a [`FrameRate`], a plain `f64` such as `25.0` or `29.97`
+/// (converted by `From<f64>`), `None` to have it measured, or a signal of one of those.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FpsProp(MaybeProp<FrameRate>);
+
+impl FpsProp {
+    pub fn get(&self) -> Option<FrameRate> {
+        self.0.get()
+    }
+}
+
+impl From<FrameRate> for FpsProp {
+    fn from(fps: FrameRate) -> Self {
+        Self(fps.into())
+    }
+}
+
+impl From<Option<FrameRate>> for FpsProp {
+    fn from(fps: Option<FrameRate>) -> Self {
+        Self(fps.into())
+    }
+}
+
+impl From<f64> for FpsProp {
+    fn from(fps: f64) -> Self {
+        Self(FrameRate::from(fps).into())
+    }
+}
+
+impl From<Signal<FrameRate>> for FpsProp {
+    fn from(fps: Signal<FrameRate>) -> Self {
+        Self(fps.into())
+    }
+}
+
+impl From<Signal<Option<FrameRate>>> for FpsProp {
+    fn from(fps: Signal<Option<FrameRate>>) -> Self {
+        Self(fps.into())
+    }
+}
+
+impl From<Signal<f64>> for FpsProp {
+    fn from(fps: Signal<f64>) -> Self {
+        Self(Signal::derive(move || FrameRate::from(fps.get())).into())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Dragging {
     Start,
@@ -21,25 +105,207 @@ enum Dragging {
 #[component]
 pub fn Video(
     #[prop(into)] src: Signal<String>,
-    #[prop(into, optional)] proxy: Signal<String>,
-    #[prop(into)] fps: Signal<f64>,
+    /// Low-res copy of `src` for smoother scrubbing; see `proxy_policy`.
+    #[prop(into, optional)]
+    proxy: Signal<String>,
+    /// When to show `proxy` instead of `src`. Follows the signal when it changes.
+    #[prop(into, optional)]
+    proxy_policy: MaybeProp<ProxyPolicy>,
+    /// Another version of the media, e.g. a later cut, to compare with `src` frame for frame
+    /// at the same frame rate. Adds a compare menu to the controls; X swaps the versions.
+    #[prop(into, optional)]
+    compare: Signal<String>,
+    /// Frame rate of the media. Measured during playback when omitted; see
+    /// [`VideoController::fps_source`].
+    #[prop(into, optional)]
+    fps: FpsProp,
+    /// Source timecode of the first frame, e.g. `01:00:00:00`. Takes precedence over `start_frame`.
+    #[prop(into, optional)]
+    start_timecode: MaybeProp<String>,
+    /// Source frame number of the first frame.
+    #[prop(into, optional)]
+    start_frame: Signal<i32>,
+    /// Handle for driving the player from the host page. One is created if omitted.
+    #[prop(optional)]
+    controller: Option<VideoController>,
+    /// Playback rate; negative plays backwards. Follows the signal when it changes.
+    #[prop(into, optional)]
+    rate: MaybeProp<f64>,
+    /// Review comments to mark on the progress bar.
+    #[prop(optional)]
+    comments: Option<Comments>,
+    /// Drawings over the picture. Adds a draw button to the controls when given.
+    #[prop(optional)]
+    annotations: Option<RwSignal<Vec<Annotation>>>,
+    /// Ticks and spans on the progress bar; see [`crate::marker`] for importing them.
+    #[prop(into, optional)]
+    markers: Signal<Vec<Marker>>,
+    /// Subtitle and caption files for the CC menu. The first marked `default` is shown on
+    /// load.
+    #[prop(into, optional)]
+    tracks: Signal<Vec<TextTrack>>,
+    /// Entries for the audio track menu. When omitted, the menu lists the media's own
+    /// tracks if it has several.
+    #[prop(into, optional)]
+    audio_tracks: Signal<Vec<AudioSource>>,
+    /// Show peak, RMS and loudness meters in the controls. Routes the audio through Web
+    /// Audio once playback starts; see [`use_audio_meter`].
+    #[prop(optional)]
+    meters: bool,
+    /// URL of an `audiowaveform` peaks file, binary `.dat` or JSON, to draw as a strip
+    /// under the progress bar. Scrolling over either zooms the timeline.
+    #[prop(into, optional)]
+    waveform: MaybeProp<String>,
+    /// URL of a WebVTT sprite-sheet thumbnail track for the scrub preview.
+    #[prop(into, optional)]
+    thumbnails: MaybeProp<String>,
+    /// Decode scrub previews from `src` when there's no thumbnail track or proxy. Costs a
+    /// second download of the full-res media.
+    #[prop(optional)]
+    preview_from_src: bool,
+    /// Called with the new frame whenever the displayed frame changes.
+    #[prop(into, optional)]
+    on_frame_change: Option<Callback<i32>>,
+    #[prop(into, optional)] on_play: Option<Callback<()>>,
+    #[prop(into, optional)] on_pause: Option<Callback<()>>,
+    #[prop(into, optional)] on_ended: Option<Callback<()>>,
+    /// Called with the frame a seek landed on. Not called for each frame of a scrub.
+    #[prop(into, optional)]
+    on_seek: Option<Callback<i32>>,
+    /// Called with the duration in seconds once it is known.
+    #[prop(into, optional)]
+    on_loaded_metadata: Option<Callback<f64>>,
+    /// Called with the browser's description of a media error, or with why
+    /// `start_timecode` couldn't be parsed.
+    #[prop(into, optional)]
+    on_error: Option<Callback<String>>,
+    #[prop(into, optional)] on_fullscreen_change: Option<Callback<bool>>,
 ) -> impl IntoView {
-    let container_ref = NodeRef::<html::Div>::new();
-    let video_ref = NodeRef::<html::Video>::new();
-    let proxy_ref = NodeRef::<html::Video>::new();
+    let controller = controller.unwrap_or_default();
+    provide_context(controller);
+    let VideoController {
+        container_ref,
+        video_ref,
+        proxy_ref,
+        audio_ref,
+        compare_ref,
+        frame,
+        end_frame,
+        is_playing,
+        buffered: preload_progress,
+        volume,
+        is_fullscreen,
+        ..
+    } = controller;
     let progress_ref = NodeRef::<html::Div>::new();
-    let (is_playing, set_is_playing) = signal(false);
+    let waveform_ref = NodeRef::<html::Div>::new();
+    let range = controller.range();
     let (dragging, set_dragging) = signal(Dragging::None);
-    let (frame, set_frame) = signal(0);
-    let (end_frame, set_end_frame) = signal(0);
-    let (preload_progress, set_preload_progress) = signal(0.0);
     let (controls_visible, set_controls_visible) = signal(false);
-    let (is_muted, set_is_muted) = signal(false);
-    let (volume, set_volume) = signal(1.0);
-    let (is_fullscreen, set_is_fullscreen) = signal(false);
+    let (video_size, set_video_size) = signal((0.0, 0.0));
+    let (is_annotating, set_is_annotating) = signal(false);
+    let (hover_frame, set_hover_frame) = signal(None::<i32>);
+    let (media_duration, set_media_duration) = signal(None::<f64>);
+    // Zoomed-in window of the progress bar and waveform; `None` shows all of the media.
+    let zoomed = RwSignal::new(None::<TimelineView>);
+    let timeline = Memo::new(move |_| {
+        let total = end_frame.get() + 1;
+        zoomed
+            .get()
+            .map_or_else(|| TimelineView::whole(total), |view| view.clamped(total))
+    });
+
+    let detected_fps =
+        use_fps_detection(video_ref, Signal::derive(move || fps.get().is_none()), src);
+    let fps_source = Memo::new(move |_| match (fps.get(), detected_fps.get()) {
+        (Some(_), _) => FpsSource::Supplied,
+        (None, Some(_)) => FpsSource::Detected,
+        (None, None) => FpsSource::Assumed,
+    });
+    let fps = Memo::new(move |_| fps.get().or(detected_fps.get()).unwrap_or_default());
+    Effect::new(move |_| controller.fps.set(fps.get()));
+    Effect::new(move |_| controller.fps_source.set(fps_source.get()));
+    Effect::new(move |_| controller.has_proxy.set(!proxy.get().is_empty()));
+    Effect::new(move |_| {
+        let has_compare = !compare.get().is_empty();
+        controller.has_compare.set(has_compare);
+        if !has_compare {
+            controller.set_compare_mode(None);
+        }
+    });
+    Effect::new(move |_| {
+        if let Some(policy) = proxy_policy.get() {
+            controller.set_proxy_policy(policy);
+        }
+    });
+    Effect::new(move |_| {
+        if let Some(rate) = rate.get() {
+            controller.set_rate(rate);
+        }
+    });
+    Effect::new(move |_| controller.set_caption_track(tracks.with(|t| default_track(t))));
+    Effect::new(move |_| {
+        let sources = audio_tracks.get();
+        if !sources.is_empty() {
+            controller.audio_sources.set(sources);
+            controller.set_audio_track(0);
+        }
+    });
 
     let container_mouse = use_mouse_in_element(container_ref);
 
+    // Source frame of media time zero. Everything user-facing is offset by this; the
+    // `<video>` clock and `frame` stay zero-based.
+    let parsed_start = Memo::new(move |_| {
+        let tc = start_timecode.get()?;
+        Some(
+            Timecode::parse(&tc, fps.get().nominal())
+                .map(|tc| tc.to_frame())
+                .map_err(|err| format!("invalid start_timecode {tc:?}: {err}")),
+        )
+    });
+    let start = Memo::new(move |_| match parsed_start.get() {
+        Some(Ok(frame)) => frame,
+        _ => start_frame.get(),
+    });
+    // Only once the rate is known, as the frames field is checked against it.
+    Effect::new(move |_| {
+        if fps_source.get() == FpsSource::Assumed {
+            return;
+        }
+        if let Some(Err(message)) = parsed_start.get() {
+            match on_error {
+                Some(on_error) => on_error.run(message),
+                None => leptos::logging::warn!("{message}"),
+            }
+        }
+    });
+    Effect::new(move |_| controller.start.set(start.get()));
+
+    Effect::new(move |_| {
+        src.track();
+        zoomed.set(None);
+    });
+    // A zoomed window pages along with the playhead.
+    Effect::new(move |_| {
+        let frame = frame.get();
+        if let Some(view) = zoomed.get_untracked() {
+            let next = view.following(end_frame.get_untracked() + 1, frame);
+            if next != view {
+                zoomed.set(Some(next));
+            }
+        }
+    });
+
+    // Left edge and width, in percent, of each version's part of the picture.
+    let pane = move |side: Side| {
+        compare::pane(
+            controller.compare_mode.get(),
+            side,
+            controller.leading_side.get(),
+        )
+    };
+
     let controls_hide_after_delay = use_debounce_fn(
         move || {
             set_controls_visible.set(false);
@@ -47,13 +313,61 @@ pub fn Video(
         2000.0, // 2 seconds
     );
 
+    let meter = meters.then(|| {
+        use_audio_meter(
+            video_ref,
+            audio_ref,
+            controller.plays_separate_audio.into(),
+            is_playing.into(),
+        )
+    });
+
+    // A separate audio file follows the video clock rather than keeping its own.
+    let resync_audio = move |media_time: f64| {
+        if !controller.plays_separate_audio.get_untracked() {
+            return;
+        }
+        if let Some(audio) = audio_ref.get_untracked() {
+            if !audio.seeking() && (audio.current_time() - media_time).abs() > AUDIO_DRIFT {
+                audio.set_current_time(media_time);
+            }
+        }
+    };
+
+    // Presented-frame callbacks report the exact frame on screen; `timeupdate` fires only a
+    // few times a second and is the fallback where they're unsupported.
+    let frame_callback_supported = use_video_frame_callback(video_ref, move |presented| {
+        if is_playing.get_untracked() {
+            resync_audio(presented.media_time);
+        }
+        if dragging.get_untracked() == Dragging::None {
+            let frame_at = fps.get_untracked().frame_at(presented.media_time);
+            frame.set(frame_at.min(end_frame.get_untracked()));
+        }
+        if controller.showing_proxy.get_untracked() && is_playing.get_untracked() {
+            if let Some(proxy) = proxy_ref.get_untracked() {
+                let drift = (proxy.current_time() - presented.media_time).abs();
+                if drift > PROXY_DRIFT_FRAMES * fps.get_untracked().frame_duration() {
+                    controller.seek_proxy(presented.media_time);
+                }
+            }
+        }
+        if controller.compare_mode.get_untracked().is_some() && is_playing.get_untracked() {
+            if let Some(compare) = compare_ref.get_untracked() {
+                let drift = (compare.current_time() - presented.media_time).abs();
+                if drift > COMPARE_DRIFT_FRAMES * fps.get_untracked().frame_duration() {
+                    controller.seek_compare(presented.media_time);
+                }
+            }
+        }
+    });
+
     let time_update = move |_| {
-        if is_playing.get() {
+        if is_playing.get() && !frame_callback_supported.get() {
             if let Some(video) = video_ref.get() {
                 let time = video.current_time();
-                let frame = f64::floor(time * fps.get()) as i32;
-                let frame = frame.min(end_frame.get());
-                set_frame.set(frame);
+                frame.set(fps.get().frame_at(time).min(end_frame.get()));
+                resync_audio(time);
             }
         }
     };
@@ -61,12 +375,98 @@ pub fn Video(
     let load_metadata = move || {
         if let Some(video) = video_ref.get() {
             let d = video.duration();
-            if d.is_finite() {
-                let total_frames = f64::floor(d * fps.get()) as i32;
-                set_end_frame.set((total_frames - 1).max(0));
+            set_media_duration.set(d.is_finite().then_some(d));
+            set_video_size.set((video.video_width() as f64, video.video_height() as f64));
+        }
+    };
+
+    // The media's own tracks are only listed once its metadata is in.
+    let (can_switch_embedded, set_can_switch_embedded) = signal(false);
+    let load_audio_tracks = move || {
+        let Some(video) = video_ref.get_untracked() else {
+            return;
+        };
+        set_can_switch_embedded.set(audio::can_switch_embedded(&video));
+        if audio_tracks.with_untracked(Vec::is_empty) {
+            let found = audio::embedded_sources(&video);
+            controller
+                .audio_sources
+                .set(if found.len() > 1 { found } else { Vec::new() });
+            controller.audio_track.set(0);
+        }
+        controller.set_audio_track(controller.audio_track.get_untracked());
+    };
+
+    // Version B plays along, muted, and otherwise holds the frame on screen. Playing
+    // backwards steps by seeking, which moves it with everything else.
+    Effect::new(move |_| {
+        let comparing = controller.compare_mode.get().is_some();
+        let play_along = comparing && is_playing.get() && controller.rate.get() > 0.0;
+        let Some(compare) = compare_ref.get() else {
+            return;
+        };
+        if play_along {
+            if let Some(video) = video_ref.get_untracked() {
+                compare.set_playback_rate(video.playback_rate());
+                controller.seek_compare(video.current_time());
+            }
+            let _ = compare.play();
+        } else {
+            let _ = compare.pause();
+            if comparing {
+                controller.seek_compare(fps.get_untracked().seek_time(frame.get_untracked()));
+            }
+        }
+    });
+
+    // A separate audio file plays along with the picture, including at speed. Runs again
+    // once a newly selected file has loaded, as loading pauses it.
+    let sync_audio_playback = move || {
+        let play_audio = controller.plays_separate_audio.get()
+            && is_playing.get()
+            && controller.rate.get() > 0.0;
+        controller.audio_track.track();
+        let Some(audio) = audio_ref.get_untracked() else {
+            return;
+        };
+        if play_audio {
+            if let Some(video) = video_ref.get_untracked() {
+                audio.set_playback_rate(video.playback_rate());
+                audio.set_current_time(video.current_time());
             }
+            let _ = audio.play();
+        } else {
+            let _ = audio.pause();
         }
     };
+    Effect::new(move |_| sync_audio_playback());
+
+    // Follows `fps`, which can change once a rate is detected.
+    Effect::new(move |_| {
+        if let Some(d) = media_duration.get() {
+            end_frame.set((fps.get().frame_at(d) - 1).max(0));
+        }
+    });
+
+    // Keep playback inside the in/out range, looping per the loop mode.
+    Effect::new(move |_| {
+        frame.track();
+        if controller.wrap() {
+            if let Some(on_ended) = on_ended {
+                on_ended.run(());
+            }
+        }
+    });
+
+    Effect::new(move |prev: Option<i32>| {
+        let frame = frame.get();
+        if prev.is_some_and(|prev| prev != frame) {
+            if let Some(on_frame_change) = on_frame_change {
+                on_frame_change.run(frame);
+            }
+        }
+        frame
+    });
 
     let preload_update = move || {
         if let Some(video) = video_ref.get() {
@@ -77,177 +477,277 @@ pub fn Video(
                 let start = vb.start(i).unwrap();
                 let end = vb.end(i).unwrap();
                 if time >= start && time <= end {
-                    set_preload_progress.set(end / d);
+                    preload_progress.set(end / d);
                     break;
                 }
             }
         }
     };
 
-    let is_ended = move || frame.get() == end_frame.get();
-
-    let seek = move |frame: i32| {
-        if let Some(video) = video_ref.get() {
-            let frame = frame.clamp(0, end_frame.get());
-            set_frame.set(frame);
-            let time = frame as f64 / fps.get();
-            video.set_current_time(time);
-            if proxy.get() == "" {
-                return;
+    // Which element is on screen. Switching to the proxy is immediate, as it has been
+    // seeked along with full-res. Switching back waits for full-res to decode the current
+    // frame, so a paused frame never comes from the proxy.
+    let wants_proxy = Memo::new(move |_| {
+        controller.has_proxy.get()
+            && match controller.proxy_policy.get() {
+                ProxyPolicy::Scrub => dragging.get() == Dragging::Move,
+                ProxyPolicy::Playback => dragging.get() == Dragging::Move || is_playing.get(),
+                ProxyPolicy::Always => true,
+                ProxyPolicy::Never => false,
             }
-            if let Some(video) = proxy_ref.get() {
-                video.set_current_time(time);
+    });
+    let is_handing_off = StoredValue::new(false);
+    Effect::new(move |_| {
+        if wants_proxy.get() {
+            is_handing_off.set_value(false);
+            controller.showing_proxy.set(true);
+        } else if controller.showing_proxy.get_untracked() {
+            is_handing_off.set_value(true);
+            if let Some(video) = video_ref.get_untracked() {
+                video.set_current_time(fps.get_untracked().seek_time(frame.get_untracked()));
             }
         }
-    };
-
-    let stop = move || {
-        if let Some(video) = video_ref.get() {
-            video.set_current_time(0.0);
-            set_frame.set(0);
-        }
-    };
+    });
 
-    let play = move || {
-        if let Some(video) = video_ref.get() {
-            if is_ended() {
-                stop();
+    // A proxy on screen during playback plays along, muted.
+    Effect::new(move |_| {
+        let play_proxy =
+            controller.showing_proxy.get() && is_playing.get() && controller.rate.get() > 0.0;
+        let Some(proxy) = proxy_ref.get_untracked() else {
+            return;
+        };
+        if play_proxy {
+            if let Some(video) = video_ref.get_untracked() {
+                proxy.set_playback_rate(video.playback_rate());
+                controller.seek_proxy(video.current_time());
             }
-            set_is_playing.set(true);
-            video.play();
+            let _ = proxy.play();
+        } else {
+            let _ = proxy.pause();
         }
-    };
+    });
 
-    let pause = move || {
+    // Report where a seek landed. With frame callbacks the presented frame reports itself,
+    // as decoded. Without them `currentTime` only echoes the time requested, so this can't
+    // confirm the decoder's frame and the requested frame is taken as shown.
+    let seeked = move |_| {
+        if is_handing_off.get_value() && !wants_proxy.get_untracked() {
+            is_handing_off.set_value(false);
+            controller.showing_proxy.set(false);
+        }
+        if dragging.get_untracked() != Dragging::None {
+            return;
+        }
         if let Some(video) = video_ref.get() {
-            set_is_playing.set(false);
-            video.pause();
+            resync_audio(video.current_time());
+            let decoded = fps.get_untracked().frame_at(video.current_time());
+            let decoded = decoded.clamp(0, end_frame.get_untracked());
+            if !frame_callback_supported.get_untracked() {
+                frame.set(decoded);
+            }
+            if let Some(on_seek) = on_seek {
+                on_seek.run(decoded);
+            }
         }
     };
 
-    let next_frame = move || {
-        seek(frame.get() + 1);
+    // Frame under `x` pixels along a progress bar `width` wide, snapped to nearby markers.
+    let scrub_frame = move |x: f64, width: f64| {
+        let view = timeline.get();
+        let frame = view.frame_at(x / width);
+        let threshold = (MARKER_SNAP_PX * view.frames / width).ceil() as i32;
+        markers.with(|markers| snap(markers, frame, threshold))
     };
 
-    let prev_frame = move || {
-        seek(frame.get() - 1);
-    };
-
-    let toggle_play = move || {
-        if is_playing.get() {
-            pause();
-        } else {
-            play();
-        }
+    let hovered_marker = move || {
+        let frame = hover_frame.get()?;
+        let width = progress_ref.get()?.client_width() as f64;
+        let threshold = (MARKER_SNAP_PX * timeline.get().frames / width).ceil() as i32;
+        markers.with(|markers| {
+            markers
+                .iter()
+                .filter(|m| {
+                    frame >= m.frame.saturating_sub(threshold)
+                        && frame <= m.last_frame().saturating_add(threshold)
+                })
+                .min_by_key(|m| m.frame.abs_diff(frame))
+                .cloned()
+        })
     };
 
+    // Dragging along the progress bar or the waveform scrubs.
     let drag_offset = StoredValue::new(0.0);
     let is_played_before_drag = StoredValue::new(false);
-    use_draggable_with_options(
-        progress_ref,
-        UseDraggableOptions::default()
-            .initial_value(Position { x: 0.0, y: 0.0 })
-            .target_offset(move |ev| (0.0, 0.0))
-            .on_start(move |ev| {
-                if let Some(p) = progress_ref.get() {
-                    if ev.event.pointer_type() == "touch" {
-                        let _ = p.focus();
-                    }
-                    if is_playing.get() {
-                        is_played_before_drag.set_value(true);
-                        pause();
+    let scrub_on = move |target: NodeRef<html::Div>| {
+        use_draggable_with_options(
+            target,
+            UseDraggableOptions::default()
+                .initial_value(Position { x: 0.0, y: 0.0 })
+                .target_offset(move |_| (0.0, 0.0))
+                .on_start(move |ev| {
+                    if let Some(p) = target.get() {
+                        if ev.event.pointer_type() == "touch" {
+                            let _ = p.focus();
+                        }
+                        if is_playing.get() {
+                            is_played_before_drag.set_value(true);
+                            controller.pause();
+                        } else {
+                            is_played_before_drag.set_value(false);
+                        }
+
+                        set_dragging.set(Dragging::Start);
+
+                        let x = ev.event.offset_x() as f64;
+                        drag_offset.set_value(x);
+
+                        controller.seek(scrub_frame(x, p.client_width() as f64));
+                        true
                     } else {
-                        is_played_before_drag.set_value(false);
+                        false
                     }
+                })
+                .on_move(move |ev| {
+                    if let Some(p) = target.get() {
+                        set_dragging.set(Dragging::Move);
+                        let x = ev.position.x + drag_offset.get_value();
 
-                    set_dragging.set(Dragging::Start);
-
-                    let x = ev.event.offset_x() as f64;
-                    drag_offset.set_value(x);
+                        controller.seek(scrub_frame(x, p.client_width() as f64));
+                        set_controls_visible.set(true);
+                    }
+                })
+                .on_end(move |_| {
+                    set_dragging.set(Dragging::None);
+                    if is_played_before_drag.get_value() {
+                        controller.play();
+                    }
+                })
+                // .stop_propagation(true)
+                .prevent_default(true),
+        );
+    };
+    scrub_on(progress_ref);
+    scrub_on(waveform_ref);
 
-                    let pos = x / p.client_width() as f64;
-                    let total_frames = end_frame.get() + 1;
-                    let frame = (pos * total_frames as f64).floor() as i32;
-                    seek(frame);
-                    true
+    // The wheel zooms the timeline about the pointer; scrolling sideways pans it.
+    let zoom_on = move |target: NodeRef<html::Div>| {
+        let _ = use_event_listener_with_options(
+            target,
+            ev::wheel,
+            move |ev| {
+                let Some(target) = target.get_untracked() else {
+                    return;
+                };
+                ev.prevent_default();
+                let total = end_frame.get_untracked() + 1;
+                let view = timeline.get_untracked();
+                let width = target.client_width().max(1) as f64;
+                let view = if ev.delta_x().abs() > ev.delta_y().abs() {
+                    view.panned(total, ev.delta_x() / width * view.frames)
                 } else {
-                    false
-                }
-            })
-            .on_move(move |ev| {
-                if let Some(p) = progress_ref.get() {
-                    set_dragging.set(Dragging::Move);
-                    let x = ev.position.x + drag_offset.get_value();
-
-                    let pos = x / p.client_width() as f64;
-                    let total_frames = end_frame.get() + 1;
-                    let frame = (pos * total_frames as f64).floor() as i32;
-                    seek(frame);
-                    set_controls_visible.set(true);
-                }
-            })
-            .on_end(move |_| {
-                set_dragging.set(Dragging::None);
-                if is_played_before_drag.get_value() {
-                    play();
-                }
-            })
-            // .stop_propagation(true)
-            .prevent_default(true),
-    );
+                    let factor = (-ev.delta_y() / WHEEL_ZOOM_PX).exp2();
+                    view.zoomed(total, factor, ev.offset_x() as f64 / width)
+                };
+                zoomed.set((!view.is_whole(total)).then_some(view));
+            },
+            UseEventListenerOptions::default().passive(false),
+        );
+    };
+    zoom_on(progress_ref);
+    zoom_on(waveform_ref);
+    // Placement of the whole media inside the bar, for laying out by whole-media fractions.
+    let zoom_left = move || {
+        let view = timeline.get();
+        format!("{}%", -100.0 * view.start / view.frames)
+    };
+    let zoom_width = move || {
+        format!(
+            "{}%",
+            100.0 * (end_frame.get() + 1) as f64 / timeline.get().frames
+        )
+    };
+
+    // JKL shuttle. Holding K turns J and L into a slow jog for as long as they're held;
+    // `jog_from` keeps the rate to return to.
+    let is_k_held = StoredValue::new(false);
+    let jog_from = StoredValue::new(None::<f64>);
+    let shuttle = move |direction: f64| {
+        if is_k_held.get_value() {
+            if jog_from.get_value().is_none() {
+                jog_from.set_value(Some(controller.rate.get_untracked()));
+            }
+            controller.set_rate(JOG_RATE * direction);
+            controller.play();
+        } else {
+            controller.shuttle(direction);
+        }
+    };
 
     let handle_keydown = move |ev: leptos::ev::KeyboardEvent| {
         ev.stop_propagation();
         // ev.prevent_default();
         match ev.key().as_str() {
-            " " => toggle_play(),
-            "ArrowLeft" => prev_frame(),
-            "ArrowRight" => next_frame(),
+            " " => controller.toggle_play(),
+            "ArrowLeft" if ev.shift_key() => {
+                if let Some(frame) = markers.with(|m| prev_marker(m, frame.get_untracked())) {
+                    controller.seek(frame);
+                }
+            }
+            "ArrowRight" if ev.shift_key() => {
+                if let Some(frame) = markers.with(|m| next_marker(m, frame.get_untracked())) {
+                    controller.seek(frame);
+                }
+            }
+            "ArrowLeft" => controller.step(-1),
+            "ArrowRight" => controller.step(1),
+            "j" | "J" if !ev.repeat() => shuttle(-1.0),
+            "l" | "L" if !ev.repeat() => shuttle(1.0),
+            "i" | "I" => controller.set_mark_in((!ev.alt_key()).then(|| frame.get_untracked())),
+            "o" | "O" => controller.set_mark_out((!ev.alt_key()).then(|| frame.get_untracked())),
+            "k" | "K" => {
+                is_k_held.set_value(true);
+                controller.pause();
+            }
+            "x" | "X" => controller.swap_sides(),
             _ => (),
         }
     };
 
+    let handle_keyup = move |ev: leptos::ev::KeyboardEvent| match ev.key().as_str() {
+        "k" | "K" => is_k_held.set_value(false),
+        "j" | "J" | "l" | "L" => {
+            if let Some(rate) = jog_from.get_value() {
+                jog_from.set_value(None);
+                controller.pause();
+                controller.set_rate(rate);
+            }
+        }
+        _ => (),
+    };
+
     let change_volume = move |ev: Event| {
         ev.stop_propagation();
-        if let Some(video) = video_ref.get() {
-            let target = event_target::<web_sys::HtmlInputElement>(&ev);
-            let vol = target.value_as_number();
-            set_volume.set(vol);
-            video.set_volume(vol);
-            set_is_muted.set(vol == 0.0);
-        }
+        let target = event_target::<web_sys::HtmlInputElement>(&ev);
+        controller.set_volume(target.value_as_number());
     };
 
     let toggle_mute = move |ev: MouseEvent| {
         ev.stop_propagation();
-        let muted = !is_muted.get();
-        set_is_muted.set(muted);
-        if let Some(video) = video_ref.get() {
-            if muted {
-                set_volume.set(0.0);
-            } else {
-                let vol = video.volume();
-                let vol = if vol == 0.0 { 1.0 } else { vol };
-                set_volume.set(vol);
-            }
-            video.set_muted(muted);
-        }
+        controller.toggle_mute();
     };
 
     let toggle_fullscreen = move |ev: MouseEvent| {
         ev.stop_propagation();
-        if let Some(el) = container_ref.get() {
-            if is_fullscreen.get() {
-                document().exit_fullscreen();
-            } else {
-                el.request_fullscreen();
-            }
-        }
+        controller.toggle_fullscreen();
     };
 
     let fullscreenchange = move |_| {
         if let Some(el) = container_ref.get() {
-            set_is_fullscreen.set(document().fullscreen_element() == Some(el.into()));
+            let fullscreen = document().fullscreen_element() == Some(el.into());
+            is_fullscreen.set(fullscreen);
+            if let Some(on_fullscreen_change) = on_fullscreen_change {
+                on_fullscreen_change.run(fullscreen);
+            }
         }
     };
 
@@ -271,53 +771,262 @@ pub fn Video(
             class="size-full flex bg-black flex-col overflow-hidden shadow-xl touch-none group"
             on:fullscreenchange=fullscreenchange
             on:keydown=handle_keydown
+            on:keyup=handle_keyup
         >
             // Video element
             <div class="relative flex-auto m-[1px] group-fullscreen:m-0">
-                <video
-                    playsinline
-                    disablepictureinpicture
-                    controlslist="nodownload"
-                    node_ref=proxy_ref
-                    src=proxy
-                    preload="auto"
-                    class="cursor-pointer absolute size-full object-contain"
-                    style:visibility=move || {
-                        if proxy.get() == "" { "hidden" } else { "visible" }
-                    }
-                    on:contextmenu=move |ev| ev.prevent_default()
-                />
+                // Version A
+                <div
+                    class="absolute inset-y-0"
+                    style:left=move || format!("{}%", pane(Side::A).0)
+                    style:width=move || format!("{}%", pane(Side::A).1)
+                >
+                    <video
+                        playsinline
+                        disablepictureinpicture
+                        controlslist="nodownload"
+                        node_ref=proxy_ref
+                        src=proxy
+                        muted
+                        preload=move || {
+                            if controller.proxy_policy.get() == ProxyPolicy::Never {
+                                "none"
+                            } else {
+                                "auto"
+                            }
+                        }
+                        class="cursor-pointer absolute size-full object-contain"
+                        style:visibility=move || {
+                            if proxy.get() == "" { "hidden" } else { "visible" }
+                        }
+                        on:contextmenu=move |ev| ev.prevent_default()
+                        on:seeked=move |_| controller.proxy_seeked()
+                        on:click=move |_| controller.toggle_play()
+                    />
 
-                <video
-                    // controls
-                    playsinline
-                    disablepictureinpicture
-                    controlslist="nodownload"
-                    node_ref=video_ref
-                    src=src
-                    // preload="auto"
-                    class="cursor-pointer absolute size-full object-contain"
-                    style:visibility=move || {
-                        if proxy.get() != "" && dragging.get() == Dragging::Move {
-                            "hidden"
-                        } else {
-                            "visible"
+                    <video
+                        // controls
+                        playsinline
+                        disablepictureinpicture
+                        controlslist="nodownload"
+                        node_ref=video_ref
+                        src=src
+                        preload=move || {
+                            if controller.proxy_policy.get() == ProxyPolicy::Always
+                                && controller.has_proxy.get()
+                            {
+                                "metadata"
+                            } else {
+                                "auto"
+                            }
+                        }
+                        class="cursor-pointer absolute size-full object-contain"
+                        style:visibility=move || {
+                            if controller.showing_proxy.get() { "hidden" } else { "visible" }
+                        }
+                        on:contextmenu=move |ev| ev.prevent_default()
+                        on:loadedmetadata=move |_| {
+                            load_metadata();
+                            load_audio_tracks();
+                            if let (Some(on_loaded_metadata), Some(video)) = (
+                                on_loaded_metadata,
+                                video_ref.get(),
+                            ) {
+                                on_loaded_metadata.run(video.duration());
+                            }
+                        }
+                        on:durationchange=move |_| load_metadata()
+                        on:timeupdate=time_update
+                        on:seeked=seeked
+                        on:click=move |_| controller.toggle_play()
+                        on:progress=move |_| preload_update()
+                        on:canplaythrough=move |_| preload_update()
+                        on:ratechange=move |_| {
+                            if let Some(video) = video_ref.get() {
+                                controller.rate.set(video.playback_rate());
+                            }
+                        }
+                        on:play=move |_| {
+                            if let Some(on_play) = on_play {
+                                on_play.run(());
+                            }
+                        }
+                        on:pause=move |_| {
+                            if let Some(on_pause) = on_pause {
+                                on_pause.run(());
+                            }
+                        }
+                        on:ended=move |_| {
+                            frame.set(end_frame.get_untracked());
+                            if controller.wrap() {
+                                if let Some(on_ended) = on_ended {
+                                    on_ended.run(());
+                                }
+                            }
+                        }
+                        on:error=move |_| {
+                            let message = video_ref
+                                .get()
+                                .and_then(|video| video.error())
+                                .map(|err| {
+                                    let message = err.message();
+                                    if message.is_empty() {
+                                        format!("media error {}", err.code())
+                                    } else {
+                                        message
+                                    }
+                                });
+                            if let (Some(on_error), Some(message)) = (on_error, message) {
+                                on_error.run(message);
+                            }
+                        }
+                    />
+                </div>
+
+                // Version B, stacked over A
+                <Show when=move || controller.has_compare.get()>
+                    <div
+                        class="absolute inset-y-0 bg-black"
+                        style:left=move || format!("{}%", pane(Side::B).0)
+                        style:width=move || format!("{}%", pane(Side::B).1)
+                        style:visibility=move || {
+                            if compare::shows_b(
+                                controller.compare_mode.get(),
+                                controller.leading_side.get(),
+                            ) {
+                                "visible"
+                            } else {
+                                "hidden"
+                            }
                         }
+                        style:clip-path=move || {
+                            compare::clip_b(
+                                controller.compare_mode.get(),
+                                controller.wipe.get(),
+                                controller.leading_side.get(),
+                            )
+                        }
+                    >
+                        <video
+                            playsinline
+                            disablepictureinpicture
+                            controlslist="nodownload"
+                            node_ref=compare_ref
+                            src=compare
+                            muted
+                            // Only buffered in full once a compare mode needs it.
+                            preload=move || {
+                                if controller.compare_mode.get().is_some() {
+                                    "auto"
+                                } else {
+                                    "metadata"
+                                }
+                            }
+                            class="cursor-pointer absolute size-full object-contain"
+                            on:contextmenu=move |ev| ev.prevent_default()
+                            on:loadedmetadata=move |_| {
+                                controller
+                                    .seek_compare(fps.get_untracked().seek_time(frame.get_untracked()));
+                            }
+                            on:seeked=move |_| controller.compare_seeked()
+                            on:click=move |_| controller.toggle_play()
+                        />
+                    </div>
+                </Show>
+
+                <Show when=move || controller.compare_mode.get() == Some(CompareMode::Difference)>
+                    <DifferenceCanvas a=video_ref b=compare_ref />
+                </Show>
+
+                <Show when=move || {
+                    matches!(controller.compare_mode.get(), Some(CompareMode::Wipe(_)))
+                }>
+                    <WipeHandle
+                        direction=Signal::derive(move || match controller.compare_mode.get() {
+                            Some(CompareMode::Wipe(direction)) => direction,
+                            _ => WipeDirection::default(),
+                        })
+                        position=controller.wipe
+                        on_change=move |position| controller.set_wipe(position)
+                    />
+                </Show>
+
+                // Annotations and captions belong to A, so they keep to its pane
+                <div
+                    class="absolute inset-y-0 pointer-events-none"
+                    style:left=move || format!("{}%", pane(Side::A).0)
+                    style:width=move || format!("{}%", pane(Side::A).1)
+                >
+                    {annotations
+                        .map(|annotations| {
+                            view! {
+                                <AnnotationLayer
+                                    annotations
+                                    frame
+                                    video_size
+                                    editing=is_annotating
+                                />
+                            }
+                        })}
+
+                    <CaptionsOverlay
+                        track=Signal::derive(move || {
+                            controller
+                                .caption_track
+                                .get()
+                                .and_then(|i| tracks.with(|tracks| tracks.get(i).cloned()))
+                        })
+                        frame
+                        fps
+                        start
+                        is_playing
+                        lifted=Signal::derive(move || {
+                            is_fullscreen.get() && controls_visible.get()
+                        })
+                    />
+                </div>
+
+                <audio
+                    node_ref=audio_ref
+                    preload="auto"
+                    src=move || {
+                        let index = controller.audio_track.get();
+                        controller
+                            .audio_sources
+                            .with(|sources| sources.get(index).and_then(|s| s.src.clone()))
                     }
-                    on:contextmenu=move |ev| ev.prevent_default()
-                    on:loadedmetadata=move |m| {
-                        log!("{:?}",m);
-                        load_metadata()
+                    on:loadedmetadata=move |_| {
+                        controller.sync_audio_output();
+                        sync_audio_playback();
                     }
-                    on:durationchange=move |_| load_metadata()
-                    on:timeupdate=time_update
-                    on:click=move |_| toggle_play()
-                    on:progress=move |_| preload_update()
-                    on:canplaythrough=move |_| preload_update()
-                    on:ratechange=move |_| log!("ratechange")
-                    on:ended=move |_| { set_is_playing.set(false) }
                 />
 
+                // Source indicator
+                <Show when=move || controller.has_proxy.get()>
+                    <div class="absolute top-2 left-2 px-2 py-0.5 rounded bg-black/60 text-white text-xs font-mono pointer-events-none">
+                        {move || if controller.showing_proxy.get() { "PROXY" } else { "FULL" }}
+                    </div>
+                </Show>
+
+                // Compare indicator
+                <Show when=move || controller.compare_mode.get().is_some()>
+                    <div class="absolute top-2 left-1/2 -translate-x-1/2 px-2 py-0.5 rounded bg-black/60 text-white text-xs font-mono pointer-events-none">
+                        {move || {
+                            controller
+                                .compare_mode
+                                .get()
+                                .map(|mode| compare::badge(mode, controller.leading_side.get()))
+                        }}
+                    </div>
+                </Show>
+
+                // Rate indicator
+                <Show when=move || controller.rate.get() != 1.0>
+                    <div class="absolute top-2 right-2 px-2 py-0.5 rounded bg-black/60 text-white text-sm font-mono pointer-events-none">
+                        {move || format_rate(controller.rate.get())}
+                    </div>
+                </Show>
+
             </div>
 
             // Controls
@@ -336,65 +1045,227 @@ pub fn Video(
             >
 
                 <div class="relative">
+                    // Comment markers
+                    {comments
+                        .map(|comments| {
+                            view! {
+                                <div class="absolute bottom-2 w-full h-1.5 overflow-hidden pointer-events-none">
+                                    <div
+                                        class="absolute inset-y-0"
+                                        style:left=zoom_left
+                                        style:width=zoom_width
+                                    >
+                                        <For
+                                            each=move || comments.list().get()
+                                            key=|comment| comment.id
+                                            let:comment
+                                        >
+                                            {
+                                                let total_frames = move || (end_frame.get() + 1) as f64;
+                                                let span = comment.end_frame.unwrap_or(comment.frame)
+                                                    - comment.frame + 1;
+                                                view! {
+                                                    <div
+                                                        title=format!("{}: {}", comment.author, comment.text)
+                                                        class="absolute bottom-0 h-1.5 min-w-1.5 rounded-full bg-yellow-400 hover:bg-yellow-300 cursor-pointer pointer-events-auto"
+                                                        style:left=move || {
+                                                            format!(
+                                                                "{}%",
+                                                                100.0 * comment.frame as f64 / total_frames(),
+                                                            )
+                                                        }
+                                                        style:width=move || {
+                                                            format!("{}%", 100.0 * span as f64 / total_frames())
+                                                        }
+                                                        on:click=move |ev| {
+                                                            ev.stop_propagation();
+                                                            controller.seek(comment.frame);
+                                                        }
+                                                    />
+                                                }
+                                            }
+                                        </For>
+                                    </div>
+                                </div>
+                            }
+                        })}
+
+                    // Hover preview
+                    <ScrubPreview
+                        frame=Signal::derive(move || {
+                            if dragging.get() == Dragging::None { hover_frame.get() } else { None }
+                        })
+                        timeline
+                        fps
+                        start
+                        track=thumbnails
+                        decoder_src=Signal::derive(move || {
+                            if thumbnails.with(Option::is_some) {
+                                String::new()
+                            } else if !proxy.get().is_empty() {
+                                proxy.get()
+                            } else if preview_from_src {
+                                src.get()
+                            } else {
+                                String::new()
+                            }
+                        })
+                        marker=Signal::derive(hovered_marker)
+                    />
+
                     // Progress bar
                     <div
                         node_ref=progress_ref
                         tabindex="-1"
                         class="absolute outline-none group/progress origin-bottom w-full h-1 expand-clickable-area hover:scale-y-200 focus:scale-y-200 bg-gray-600 group-fullscreen:bg-white/20 cursor-pointer transform transition-all duration-200"
-                    >
-                        // Preload
-                        <div
-                            class="absolute origin-left h-full w-full bg-white/20 transition-scale duration-200 pointer-events-none"
-                            style:transform=move || {
-                                format!("scaleX({})", preload_progress.get())
+                        on:pointermove=move |ev| {
+                            if let Some(p) = progress_ref.get() {
+                                let pos = ev.offset_x() as f64 / p.client_width() as f64;
+                                set_hover_frame.set(Some(timeline.get().frame_at(pos)));
                             }
-                        />
+                        }
+                        on:pointerleave=move |_| set_hover_frame.set(None)
+                    >
+                        <div class="absolute inset-0 overflow-hidden pointer-events-none">
+                            <div class="absolute inset-y-0" style:left=zoom_left style:width=zoom_width>
+                                // Preload
+                                <div
+                                    class="absolute origin-left h-full w-full bg-white/20 transition-scale duration-200 pointer-events-none"
+                                    style:transform=move || {
+                                        format!("scaleX({})", preload_progress.get())
+                                    }
+                                />
 
-                        // Progress
-                        <div
-                            class="absolute origin-left h-full w-full bg-blue-500 pointer-events-none"
-                            style:transform=move || {
-                                format!(
-                                    "scaleX({})",
-                                    frame.get() as f64 / (end_frame.get() + 1) as f64,
-                                )
-                            }
-                        />
+                                // Markers
+                                <For
+                                    each=move || markers.get().into_iter().enumerate()
+                                    key=|(i, marker)| (*i, marker.clone())
+                                    let:((_, marker))
+                                >
+                                    {
+                                        let total_frames = move || (end_frame.get() + 1) as f64;
+                                        let span = marker.last_frame() - marker.frame + 1;
+                                        view! {
+                                            <div
+                                                class="absolute h-full min-w-0.5 opacity-80 pointer-events-none"
+                                                style:background-color=marker.color().to_string()
+                                                style:left=move || {
+                                                    format!("{}%", 100.0 * marker.frame as f64 / total_frames())
+                                                }
+                                                style:width=move || {
+                                                    if marker.end_frame.is_some() {
+                                                        format!("{}%", 100.0 * span as f64 / total_frames())
+                                                    } else {
+                                                        "2px".to_string()
+                                                    }
+                                                }
+                                            />
+                                        }
+                                    }
+                                </For>
 
-                        // Cursor
-                        <div
-                            class="absolute origin-left h-full w-full bg-blue-300 pointer-events-none"
-                            style:transform=move || {
-                                let total_frames = (end_frame.get() + 1) as f64;
-                                format!(
-                                    "translateX({}%) scaleX({})",
-                                    (100 * frame.get()) as f64 / total_frames,
-                                    total_frames.recip(),
-                                )
-                            }
-                        />
+                                // In/out range
+                                <Show when=move || {
+                                    controller.mark_in.get().is_some() || controller.mark_out.get().is_some()
+                                }>
+                                    <div
+                                        class="absolute h-full bg-yellow-400/40 pointer-events-none"
+                                        style:left=move || {
+                                            let total_frames = (end_frame.get() + 1) as f64;
+                                            format!("{}%", 100.0 * range.get().0 as f64 / total_frames)
+                                        }
+                                        style:width=move || {
+                                            let total_frames = (end_frame.get() + 1) as f64;
+                                            let (first, last) = range.get();
+                                            format!("{}%", 100.0 * (last - first + 1) as f64 / total_frames)
+                                        }
+                                    />
+                                </Show>
+
+                                // Progress
+                                <div
+                                    class="absolute origin-left h-full w-full bg-blue-500 pointer-events-none"
+                                    style:transform=move || {
+                                        format!(
+                                            "scaleX({})",
+                                            frame.get() as f64 / (end_frame.get() + 1) as f64,
+                                        )
+                                    }
+                                />
 
+                                // Cursor
+                                <div
+                                    class="absolute origin-left h-full w-full bg-blue-300 pointer-events-none"
+                                    style:transform=move || {
+                                        let total_frames = (end_frame.get() + 1) as f64;
+                                        format!(
+                                            "translateX({}%) scaleX({})",
+                                            (100 * frame.get()) as f64 / total_frames,
+                                            total_frames.recip(),
+                                        )
+                                    }
+                                />
+                            </div>
+                        </div>
                     </div>
 
+                    // Waveform
+                    <Show when=move || waveform.with(Option::is_some)>
+                        <Waveform
+                            src=Signal::derive(move || waveform.get().unwrap_or_default())
+                            view=timeline
+                            fps
+                            frame
+                            node_ref=waveform_ref
+                        />
+                    </Show>
+
                     // Control buttons
                     <div class="flex items-center justify-between px-1 pb-2 pt-4 bottom-0">
                         // Left side
                         <div class="flex items-center space-x-4">
                             // Play/Pause button
                             <button
-                                on:click=move |_| toggle_play()
+                                on:click=move |_| controller.toggle_play()
                                 on:keydown=move |ev| ev.prevent_default()
                                 class="text-white hover:text-blue-400  hover:bg-white/10 0transition-colors p-1 rounded cursor-pointer"
                             >
                                 <PlayPause play=is_playing />
                             </button>
+
+                            // Loop mode
+                            <button
+                                on:click=move |ev| {
+                                    ev.stop_propagation();
+                                    controller.loop_mode.update(|mode| *mode = mode.next());
+                                }
+                                on:keydown=move |ev| ev.prevent_default()
+                                title=move || format!("Loop: {:?}", controller.loop_mode.get())
+                                class=move || {
+                                    format!(
+                                        "hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer {}",
+                                        if controller.loop_mode.get() == LoopMode::Off {
+                                            "text-gray-400"
+                                        } else {
+                                            "text-white"
+                                        },
+                                    )
+                                }
+                            >
+                                <Loop mode=controller.loop_mode />
+                            </button>
                         </div>
 
                         // Center
                         <div class="flex items-center space-x-4">
                             // Time display
                             <div class="flex items-center text-white text-sm font-mono">
-                                <span>{move || timecode(frame.get(), fps.get())}</span>
+                                <TimecodeInput
+                                    frame=frame
+                                    fps=fps
+                                    start=start
+                                    on_seek=move |f| controller.seek(f)
+                                />
                                 <span class="mx-1 text-gray-400">/</span>
                                 <span class="text-gray-400">{frame}</span>
                             </div>
@@ -402,6 +1273,74 @@ pub fn Video(
 
                         // Right side
                         <div class="flex items-center space-x-4">
+                            // Annotate
+                            {annotations
+                                .map(|_| {
+                                    view! {
+                                        <button
+                                            on:click=move |ev| {
+                                                ev.stop_propagation();
+                                                if !is_annotating.get() {
+                                                    controller.pause();
+                                                }
+                                                set_is_annotating.update(|on| *on = !*on);
+                                            }
+                                            on:keydown=move |ev| ev.prevent_default()
+                                            title="Draw"
+                                            class=move || {
+                                                format!(
+                                                    "hover:text-blue-400 hover:bg-white/10 transition-colors p-1 rounded cursor-pointer {}",
+                                                    if is_annotating.get() {
+                                                        "text-blue-400"
+                                                    } else {
+                                                        "text-white"
+                                                    },
+                                                )
+                                            }
+                                        >
+                                            <Draw />
+                                        </button>
+                                    }
+                                })}
+
+                            // Subtitles
+                            <Show when=move || tracks.with(|tracks| !tracks.is_empty())>
+                                <CaptionsMenu
+                                    tracks
+                                    selected=controller.caption_track
+                                    on_change=move |track| controller.set_caption_track(track)
+                                />
+                            </Show>
+
+                            // Levels
+                            {meter.map(|meter| view! { <AudioMeters meter /> })}
+
+                            // Audio tracks
+                            <Show when=move || {
+                                controller.audio_sources.with(|sources| sources.len() > 1)
+                            }>
+                                <AudioTrackMenu
+                                    tracks=controller.audio_sources
+                                    selected=controller.audio_track
+                                    can_switch_embedded
+                                    on_change=move |track| controller.set_audio_track(track)
+                                />
+                            </Show>
+
+                            // Compare
+                            <Show when=move || controller.has_compare.get()>
+                                <CompareMenu
+                                    mode=controller.compare_mode
+                                    on_change=move |mode| controller.set_compare_mode(mode)
+                                />
+                            </Show>
+
+                            // Playback speed
+                            <RateMenu
+                                rate=controller.rate
+                                on_change=move |rate| controller.set_rate(rate)
+                            />
+
                             // Volume control
                             <div class="flex items-center">
                                 <button
@@ -435,14 +1374,3 @@ pub fn Video(
         </div>
     }
 }
-
-fn timecode(frame: i32, fps: f64) -> String {
-    let fframe = frame as f64;
-    let time = fframe / fps;
-    let hours = (time / 360.0).floor() as i32;
-    let minutes = (time / 60.0).floor() as i32;
-    let seconds = (time % 60.0).floor() as i32;
-    let frame = (fframe % fps) as i32;
-    let pad = (fps as i32).to_string().len();
-    format!("{hours:02}:{minutes:02}:{seconds:02}:{frame:0>pad$}")
-}
@@ -0,0 +1,663 @@
+use crate::audio::{self, embedded_index, AudioSource};
+use crate::compare::{CompareMode, Side};
+use crate::frame_rate::FrameRate;
+use leptos::prelude::*;
+use leptos::*;
+use std::cell::Cell;
+use std::time::Duration;
+use web_sys::js_sys::Date;
+use web_sys::HtmlVideoElement;
+
+/// Handle for driving a [`Video`](super::video::Video) from outside it.
+///
+/// Create one with [`VideoController::new`] and pass it as the `controller` prop, or call
+/// `use_context::<VideoController>()` from a component rendered inside the player. It is
+/// `Copy`, so hand it to as many shot lists and panels as need it.
+///
+/// Frame numbers are zero-based media frames, the same as the `<video>` clock. Rates are
+/// signed: negative rates play backwards.
+#[derive(Debug, Clone, Copy)]
+pub struct VideoController {
+    pub(crate) container_ref: NodeRef<html::Div>,
+    pub(crate) video_ref: NodeRef<html::Video>,
+    pub(crate) proxy_ref: NodeRef<html::Video>,
+    /// Plays audio sources that are separate files.
+    pub(crate) audio_ref: NodeRef<html::Audio>,
+    pub(crate) has_proxy: RwSignal<bool>,
+    pub(crate) proxy_policy: RwSignal<ProxyPolicy>,
+    pub(crate) showing_proxy: RwSignal<bool>,
+    pub(crate) fps: RwSignal<FrameRate>,
+    pub(crate) fps_source: RwSignal<FpsSource>,
+    pub(crate) start: RwSignal<i32>,
+    pub(crate) frame: RwSignal<i32>,
+    pub(crate) end_frame: RwSignal<i32>,
+    pub(crate) is_playing: RwSignal<bool>,
+    pub(crate) buffered: RwSignal<f64>,
+    pub(crate) volume: RwSignal<f64>,
+    pub(crate) is_muted: RwSignal<bool>,
+    pub(crate) audio_sources: RwSignal<Vec<AudioSource>>,
+    /// Index into `audio_sources` of the track heard.
+    pub(crate) audio_track: RwSignal<usize>,
+    /// Whether the track heard is a separate file rather than part of the media.
+    pub(crate) plays_separate_audio: RwSignal<bool>,
+    pub(crate) rate: RwSignal<f64>,
+    pub(crate) is_fullscreen: RwSignal<bool>,
+    pub(crate) mark_in: RwSignal<Option<i32>>,
+    pub(crate) mark_out: RwSignal<Option<i32>>,
+    pub(crate) loop_mode: RwSignal<LoopMode>,
+    /// Index into the player's `tracks` of the captions shown, if any.
+    pub(crate) caption_track: RwSignal<Option<usize>>,
+    /// Version B of a comparison, held on A's frame.
+    pub(crate) compare_ref: NodeRef<html::Video>,
+    pub(crate) has_compare: RwSignal<bool>,
+    /// `None` while not comparing.
+    pub(crate) compare_mode: RwSignal<Option<CompareMode>>,
+    pub(crate) leading_side: RwSignal<Side>,
+    /// How far across the picture a wipe splits it, `0.0..=1.0`.
+    pub(crate) wipe: RwSignal<f64>,
+    /// Timer stepping frames backwards while playing at a negative rate.
+    reverse: StoredValue<Option<IntervalHandle>>,
+    /// Newest proxy seek waiting for the one in flight to finish.
+    queued_proxy_seek: StoredValue<Option<f64>>,
+    /// Newest seek of version B waiting for the one in flight to finish.
+    queued_compare_seek: StoredValue<Option<f64>>,
+}
+
+/// Where the player's frame rate came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FpsSource {
+    /// Passed in as the `fps` prop.
+    #[default]
+    Supplied,
+    /// Measured from playback and snapped to a standard rate.
+    Detected,
+    /// No `fps` prop and nothing measured yet; the default rate is in use.
+    Assumed,
+}
+
+/// When the player shows the low-res `proxy` instead of `src`.
+///
+/// This only changes what's on screen. Full-res still plays and seeks underneath, as the
+/// clock and the audio, so it is decoded at full cost whichever is shown; the proxy's
+/// cheaper frames just arrive sooner while full-res catches up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ProxyPolicy {
+    /// While dragging the playhead.
+    #[default]
+    Scrub,
+    /// While dragging or playing; paused frames come from full-res.
+    Playback,
+    /// Whenever there is a proxy, paused frames included.
+    Always,
+    Never,
+}
+
+/// What playback does on reaching the end of the in/out range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LoopMode {
+    /// Stop on the last frame.
+    #[default]
+    Off,
+    /// Jump back to the other end and carry on.
+    Loop,
+    /// Reverse direction at each end.
+    PingPong,
+}
+
+impl LoopMode {
+    /// Off → Loop → PingPong → Off, for a single toggle button.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Loop,
+            Self::Loop => Self::PingPong,
+            Self::PingPong => Self::Off,
+        }
+    }
+}
+
+/// Rates the J and L keys shuttle through, in each direction.
+pub const SHUTTLE_RATES: [f64; 4] = [1.0, 2.0, 4.0, 8.0];
+
+/// Rates offered in the speed menu.
+pub const MENU_RATES: [f64; 7] = [0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// Rate K+J and K+L jog at.
+pub const JOG_RATE: f64 = 0.25;
+
+/// Shortest interval between emulated reverse steps; faster rates skip frames instead.
+const REVERSE_TICK: Duration = Duration::from_millis(40);
+
+fn clamp_range(end_frame: i32, mark_in: Option<i32>, mark_out: Option<i32>) -> (i32, i32) {
+    let first = mark_in.unwrap_or(0).clamp(0, end_frame);
+    let last = mark_out.unwrap_or(end_frame).clamp(first, end_frame);
+    (first, last)
+}
+
+/// Seek `video` to `time`, or if it's already seeking, queue `time` in place of any
+/// request queued before.
+fn seek_or_queue(video: &HtmlVideoElement, queued: StoredValue<Option<f64>>, time: f64) {
+    if video.seeking() {
+        queued.set_value(Some(time));
+    } else {
+        video.set_current_time(time);
+    }
+}
+
+/// `1x`, `0.25x`, `-4x`.
+pub fn format_rate(rate: f64) -> String {
+    format!("{rate}x")
+}
+
+impl Default for VideoController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VideoController {
+    pub fn new() -> Self {
+        Self {
+            container_ref: NodeRef::new(),
+            video_ref: NodeRef::new(),
+            proxy_ref: NodeRef::new(),
+            audio_ref: NodeRef::new(),
+            has_proxy: RwSignal::new(false),
+            proxy_policy: RwSignal::new(ProxyPolicy::default()),
+            showing_proxy: RwSignal::new(false),
+            fps: RwSignal::new(FrameRate::default()),
+            fps_source: RwSignal::new(FpsSource::default()),
+            start: RwSignal::new(0),
+            frame: RwSignal::new(0),
+            end_frame: RwSignal::new(0),
+            is_playing: RwSignal::new(false),
+            buffered: RwSignal::new(0.0),
+            volume: RwSignal::new(1.0),
+            is_muted: RwSignal::new(false),
+            audio_sources: RwSignal::new(Vec::new()),
+            audio_track: RwSignal::new(0),
+            plays_separate_audio: RwSignal::new(false),
+            rate: RwSignal::new(1.0),
+            is_fullscreen: RwSignal::new(false),
+            mark_in: RwSignal::new(None),
+            mark_out: RwSignal::new(None),
+            loop_mode: RwSignal::new(LoopMode::Off),
+            caption_track: RwSignal::new(None),
+            compare_ref: NodeRef::new(),
+            has_compare: RwSignal::new(false),
+            compare_mode: RwSignal::new(None),
+            leading_side: RwSignal::new(Side::A),
+            wipe: RwSignal::new(0.5),
+            reverse: StoredValue::new(None),
+            queued_proxy_seek: StoredValue::new(None),
+            queued_compare_seek: StoredValue::new(None),
+        }
+    }
+
+    /// Frame currently on screen.
+    pub fn frame(&self) -> Signal<i32> {
+        self.frame.into()
+    }
+
+    /// Last frame of the media; 0 until metadata has loaded.
+    pub fn end_frame(&self) -> Signal<i32> {
+        self.end_frame.into()
+    }
+
+    /// Duration in seconds, rounded to whole frames.
+    pub fn duration(&self) -> Signal<f64> {
+        let (fps, end_frame) = (self.fps, self.end_frame);
+        Signal::derive(move || fps.get().time_of(end_frame.get() + 1))
+    }
+
+    pub fn fps(&self) -> Signal<FrameRate> {
+        self.fps.into()
+    }
+
+    /// Whether [`fps`](Self::fps) was supplied, detected or is still a guess.
+    pub fn fps_source(&self) -> Signal<FpsSource> {
+        self.fps_source.into()
+    }
+
+    pub fn proxy_policy(&self) -> Signal<ProxyPolicy> {
+        self.proxy_policy.into()
+    }
+
+    pub fn set_proxy_policy(&self, policy: ProxyPolicy) {
+        self.proxy_policy.set(policy);
+    }
+
+    /// Whether the proxy, rather than full-res, is on screen.
+    pub fn is_showing_proxy(&self) -> Signal<bool> {
+        self.showing_proxy.into()
+    }
+
+    /// Source frame of media frame zero, from the player's `start_timecode`/`start_frame`.
+    pub fn start(&self) -> Signal<i32> {
+        self.start.into()
+    }
+
+    pub fn is_playing(&self) -> Signal<bool> {
+        self.is_playing.into()
+    }
+
+    /// Signed playback rate; 1.0 is normal speed.
+    pub fn rate(&self) -> Signal<f64> {
+        self.rate.into()
+    }
+
+    /// Fraction of the media buffered ahead of the playhead, `0.0..=1.0`.
+    pub fn buffered(&self) -> Signal<f64> {
+        self.buffered.into()
+    }
+
+    pub fn volume(&self) -> Signal<f64> {
+        self.volume.into()
+    }
+
+    pub fn is_muted(&self) -> Signal<bool> {
+        self.is_muted.into()
+    }
+
+    /// The player's `audio_tracks`, or the media's own tracks when there are several and
+    /// none were given.
+    pub fn audio_tracks(&self) -> Signal<Vec<AudioSource>> {
+        self.audio_sources.into()
+    }
+
+    /// Index into [`audio_tracks`](Self::audio_tracks) of the track heard.
+    pub fn audio_track(&self) -> Signal<usize> {
+        self.audio_track.into()
+    }
+
+    pub fn is_fullscreen(&self) -> Signal<bool> {
+        self.is_fullscreen.into()
+    }
+
+    pub fn mark_in(&self) -> Signal<Option<i32>> {
+        self.mark_in.into()
+    }
+
+    pub fn mark_out(&self) -> Signal<Option<i32>> {
+        self.mark_out.into()
+    }
+
+    pub fn loop_mode(&self) -> Signal<LoopMode> {
+        self.loop_mode.into()
+    }
+
+    /// Index into the player's `tracks` of the captions shown; `None` when they're off.
+    pub fn caption_track(&self) -> Signal<Option<usize>> {
+        self.caption_track.into()
+    }
+
+    /// How versions A and B share the picture; `None` while not comparing.
+    pub fn compare_mode(&self) -> Signal<Option<CompareMode>> {
+        self.compare_mode.into()
+    }
+
+    /// The version shown in [`CompareMode::Flip`], and first in the other modes.
+    pub fn leading_side(&self) -> Signal<Side> {
+        self.leading_side.into()
+    }
+
+    /// Where a wipe splits the picture, as a fraction of its width or height.
+    pub fn wipe(&self) -> Signal<f64> {
+        self.wipe.into()
+    }
+
+    /// First and last frame playback and seeking are confined to: the marks, or the whole
+    /// media where unset.
+    pub fn range(&self) -> Signal<(i32, i32)> {
+        let (end_frame, mark_in, mark_out) = (self.end_frame, self.mark_in, self.mark_out);
+        Signal::derive(move || clamp_range(end_frame.get(), mark_in.get(), mark_out.get()))
+    }
+
+    fn range_untracked(&self) -> (i32, i32) {
+        clamp_range(
+            self.end_frame.get_untracked(),
+            self.mark_in.get_untracked(),
+            self.mark_out.get_untracked(),
+        )
+    }
+
+    /// Whether the playhead is on the last frame of the range.
+    pub fn is_ended(&self) -> bool {
+        self.frame.get_untracked() >= self.range_untracked().1
+    }
+
+    /// Set mark-in, or clear it with `None`. A mark-in after mark-out clears mark-out.
+    pub fn set_mark_in(&self, frame: Option<i32>) {
+        let frame = frame.map(|frame| frame.clamp(0, self.end_frame.get_untracked()));
+        if let (Some(frame), Some(out)) = (frame, self.mark_out.get_untracked()) {
+            if frame > out {
+                self.mark_out.set(None);
+            }
+        }
+        self.mark_in.set(frame);
+    }
+
+    /// Set mark-out, or clear it with `None`. A mark-out before mark-in clears mark-in.
+    pub fn set_mark_out(&self, frame: Option<i32>) {
+        let frame = frame.map(|frame| frame.clamp(0, self.end_frame.get_untracked()));
+        if let (Some(frame), Some(mark_in)) = (frame, self.mark_in.get_untracked()) {
+            if frame < mark_in {
+                self.mark_in.set(None);
+            }
+        }
+        self.mark_out.set(frame);
+    }
+
+    pub fn clear_marks(&self) {
+        self.mark_in.set(None);
+        self.mark_out.set(None);
+    }
+
+    pub fn set_loop_mode(&self, mode: LoopMode) {
+        self.loop_mode.set(mode);
+    }
+
+    pub fn set_caption_track(&self, track: Option<usize>) {
+        self.caption_track.set(track);
+    }
+
+    /// Start comparing against the player's `compare` version, or stop with `None`. Ignored
+    /// when there's no version B.
+    pub fn set_compare_mode(&self, mode: Option<CompareMode>) {
+        if mode.is_none() || self.has_compare.get_untracked() {
+            self.compare_mode.set(mode);
+        }
+    }
+
+    /// Swap which version leads: flip between them, or trade sides of a wipe or split.
+    pub fn swap_sides(&self) {
+        self.leading_side.update(|side| *side = side.other());
+    }
+
+    pub fn set_wipe(&self, position: f64) {
+        self.wipe.set(position.clamp(0.0, 1.0));
+    }
+
+    pub fn play(&self) {
+        if let Some(video) = self.video_ref.get_untracked() {
+            if self.rate.get_untracked() < 0.0 {
+                let (first, last) = self.range_untracked();
+                if self.frame.get_untracked() <= first {
+                    self.seek(last);
+                }
+                self.is_playing.set(true);
+                let _ = video.pause();
+                self.start_reverse();
+                return;
+            }
+            let (first, last) = self.range_untracked();
+            let frame = self.frame.get_untracked();
+            if frame < first || frame >= last {
+                self.seek(first);
+            }
+            self.stop_reverse();
+            self.is_playing.set(true);
+            let _ = video.play();
+        }
+    }
+
+    pub fn pause(&self) {
+        self.stop_reverse();
+        if let Some(video) = self.video_ref.get_untracked() {
+            self.is_playing.set(false);
+            let _ = video.pause();
+        }
+    }
+
+    pub fn toggle_play(&self) {
+        if self.is_playing.get_untracked() {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Seek to `frame`, clamped to the in/out range.
+    pub fn seek(&self, frame: i32) {
+        if let Some(video) = self.video_ref.get_untracked() {
+            let (first, last) = self.range_untracked();
+            let frame = frame.clamp(first, last);
+            self.frame.set(frame);
+            let time = self.fps.get_untracked().seek_time(frame);
+            video.set_current_time(time);
+            if self.has_proxy.get_untracked()
+                && self.proxy_policy.get_untracked() != ProxyPolicy::Never
+            {
+                self.seek_proxy(time);
+            }
+            if self.compare_mode.get_untracked().is_some() {
+                self.seek_compare(time);
+            }
+        }
+    }
+
+    /// Seek the proxy one request at a time, keeping only the newest queued, so a fast drag
+    /// doesn't pile up decodes.
+    pub(crate) fn seek_proxy(&self, time: f64) {
+        if let Some(proxy) = self.proxy_ref.get_untracked() {
+            seek_or_queue(&proxy, self.queued_proxy_seek, time);
+        }
+    }
+
+    /// Start the queued proxy seek, if any; call when the proxy finishes seeking.
+    pub(crate) fn proxy_seeked(&self) {
+        if let Some(time) = self
+            .queued_proxy_seek
+            .try_update_value(Option::take)
+            .flatten()
+        {
+            self.seek_proxy(time);
+        }
+    }
+
+    /// Seek version B, queueing like [`seek_proxy`](Self::seek_proxy).
+    pub(crate) fn seek_compare(&self, time: f64) {
+        if let Some(compare) = self.compare_ref.get_untracked() {
+            seek_or_queue(&compare, self.queued_compare_seek, time);
+        }
+    }
+
+    /// Start the queued seek of version B, if any; call when it finishes seeking.
+    pub(crate) fn compare_seeked(&self) {
+        if let Some(time) = self
+            .queued_compare_seek
+            .try_update_value(Option::take)
+            .flatten()
+        {
+            self.seek_compare(time);
+        }
+    }
+
+    /// Seek to the frame shown at media time `time` seconds.
+    pub fn seek_time(&self, time: f64) {
+        self.seek(self.fps.get_untracked().frame_at(time));
+    }
+
+    /// Move `frames` forward, or back when negative.
+    pub fn step(&self, frames: i32) {
+        self.seek(self.frame.get_untracked() + frames);
+    }
+
+    /// Set the playback rate. Negative rates are emulated by stepping frames backwards, as
+    /// `<video>` can't play in reverse; zero is treated as 1.
+    pub fn set_rate(&self, rate: f64) {
+        let rate = if rate == 0.0 || !rate.is_finite() {
+            1.0
+        } else {
+            rate
+        };
+        let was_reverse = self.rate.get_untracked() < 0.0;
+        self.rate.set(rate);
+        if rate > 0.0 {
+            if let Some(video) = self.video_ref.get_untracked() {
+                video.set_playback_rate(rate);
+            }
+        }
+        if self.is_playing.get_untracked() && was_reverse != (rate < 0.0) {
+            self.play();
+        }
+    }
+
+    /// J/L shuttle: start playing in `direction` (+1 or -1) at 1x, or double the speed if
+    /// already going that way, up to 8x.
+    pub fn shuttle(&self, direction: f64) {
+        let rate = self.rate.get_untracked();
+        let speed = if self.is_playing.get_untracked() && rate * direction > 0.0 {
+            SHUTTLE_RATES
+                .iter()
+                .copied()
+                .find(|&speed| speed > rate.abs())
+                .unwrap_or(SHUTTLE_RATES[SHUTTLE_RATES.len() - 1])
+        } else {
+            SHUTTLE_RATES[0]
+        };
+        self.set_rate(speed * direction.signum());
+        self.play();
+    }
+
+    fn start_reverse(&self) {
+        self.stop_reverse();
+        let this = *self;
+        let last_tick = Cell::new(Date::now());
+        let owed = Cell::new(0.0);
+        let handle = set_interval_with_handle(
+            move || {
+                let now = Date::now();
+                let elapsed = (now - last_tick.replace(now)) / 1000.0;
+                let rate = this.rate.get_untracked();
+                owed.set(owed.get() + elapsed * -rate * this.fps.get_untracked().as_f64());
+                let frames = owed.get().floor();
+                if frames < 1.0 {
+                    return;
+                }
+                owed.set(owed.get() - frames);
+                this.seek(this.frame.get_untracked() - frames as i32);
+                this.wrap();
+            },
+            REVERSE_TICK,
+        );
+        self.reverse.set_value(handle.ok());
+    }
+
+    /// Apply the loop mode if playback has reached the end of the range it's heading for.
+    /// Returns whether playback stopped there.
+    pub(crate) fn wrap(&self) -> bool {
+        if !self.is_playing.get_untracked() {
+            return false;
+        }
+        let (first, last) = self.range_untracked();
+        let rate = self.rate.get_untracked();
+        let frame = self.frame.get_untracked();
+        let (from, to) = if rate > 0.0 {
+            if frame < last {
+                return false;
+            }
+            (first, last)
+        } else {
+            if frame > first {
+                return false;
+            }
+            (last, first)
+        };
+        match self.loop_mode.get_untracked() {
+            LoopMode::Off => {
+                self.pause();
+                self.seek(to);
+                true
+            }
+            LoopMode::Loop => {
+                self.seek(from);
+                self.play();
+                false
+            }
+            LoopMode::PingPong => {
+                self.set_rate(-rate);
+                false
+            }
+        }
+    }
+
+    fn stop_reverse(&self) {
+        if let Some(handle) = self.reverse.try_update_value(Option::take).flatten() {
+            handle.clear();
+        }
+    }
+
+    /// Switch to entry `index` of [`audio_tracks`](Self::audio_tracks). Ignored if there's
+    /// no such entry, or it's a track of the media and the browser can't switch those.
+    pub fn set_audio_track(&self, index: usize) {
+        let Some(video) = self.video_ref.get_untracked() else {
+            return;
+        };
+        if index >= self.audio_sources.with_untracked(Vec::len) {
+            return;
+        }
+        let embedded = self
+            .audio_sources
+            .with_untracked(|sources| embedded_index(sources, index));
+        if let Some(embedded) = embedded {
+            // Without `audioTracks` only the media's default track can be heard.
+            if !audio::enable_embedded(&video, embedded) && embedded > 0 {
+                return;
+            }
+        }
+        self.audio_track.set(index);
+        self.plays_separate_audio.set(embedded.is_none());
+        self.sync_audio_output();
+    }
+
+    /// Route sound to whichever of the video and the separate audio element plays the
+    /// selected track, at the current volume.
+    pub(crate) fn sync_audio_output(&self) {
+        let Some(video) = self.video_ref.get_untracked() else {
+            return;
+        };
+        let muted = self.is_muted.get_untracked();
+        let separate = self.plays_separate_audio.get_untracked();
+        video.set_muted(muted || separate);
+        if let Some(audio) = self.audio_ref.get_untracked() {
+            audio.set_volume(video.volume());
+            audio.set_muted(muted || !separate);
+        }
+    }
+
+    /// Set the volume, `0.0..=1.0`. Zero mutes.
+    pub fn set_volume(&self, volume: f64) {
+        if let Some(video) = self.video_ref.get_untracked() {
+            let volume = volume.clamp(0.0, 1.0);
+            self.volume.set(volume);
+            video.set_volume(volume);
+            self.is_muted.set(volume == 0.0);
+            self.sync_audio_output();
+        }
+    }
+
+    pub fn toggle_mute(&self) {
+        let muted = !self.is_muted.get_untracked();
+        self.is_muted.set(muted);
+        if let Some(video) = self.video_ref.get_untracked() {
+            if muted {
+                self.volume.set(0.0);
+            } else {
+                let vol = video.volume();
+                let vol = if vol == 0.0 { 1.0 } else { vol };
+                self.volume.set(vol);
+            }
+            self.sync_audio_output();
+        }
+    }
+
+    pub fn toggle_fullscreen(&self) {
+        if let Some(el) = self.container_ref.get_untracked() {
+            if self.is_fullscreen.get_untracked() {
+                document().exit_fullscreen();
+            } else {
+                let _ = el.request_fullscreen();
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::fetch::fetch_bytes;
+use crate::frame_rate::FrameRate;
+use crate::peaks::Peaks;
+use crate::timeline::TimelineView;
+use leptos::prelude::*;
+use leptos::*;
+use leptos_use::{use_element_size, UseElementSizeReturn};
+use std::fmt::Write;
+
+/// Waveform of the audio in `src`, an `audiowaveform` peaks file, across the frames in
+/// `view` so it lines up with the progress bar above it.
+///
+/// `node_ref` is the strip itself, for the player to attach its scrubbing to.
+#[component]
+pub fn Waveform(
+    /// URL of a peaks file: binary for `.dat`, JSON otherwise.
+    #[prop(into)]
+    src: Signal<String>,
+    #[prop(into)] view: Signal<TimelineView>,
+    #[prop(into)] fps: Signal<FrameRate>,
+    #[prop(into)] frame: Signal<i32>,
+    node_ref: NodeRef<html::Div>,
+) -> impl IntoView {
+    let UseElementSizeReturn { width, .. } = use_element_size(node_ref);
+
+    let peaks = LocalResource::new(move || {
+        let url = src.get();
+        async move {
+            let bytes = fetch_bytes(&url).await.ok()?;
+            Peaks::parse(&bytes, &url).ok()
+        }
+    });
+
+    // One column per pixel, in a -1..1 tall box stretched to fit.
+    let path = move || {
+        let columns = width.get().floor() as usize;
+        let (view, fps) = (view.get(), fps.get().as_f64());
+        peaks.with(|peaks| {
+            let peaks = peaks.as_ref()?.as_ref()?;
+            let mut d = String::new();
+            for x in 0..columns {
+                let start = view.start + view.frames * x as f64 / columns as f64;
+                let end = view.start + view.frames * (x + 1) as f64 / columns as f64;
+                if let Some((min, max)) = peaks.range(start / fps, end / fps) {
+                    let _ = write!(d, "M{}.5 {:.3}V{:.3}", x, -max, -min.min(max - 0.01));
+                }
+            }
+            Some(d)
+        })
+    };
+
+    view! {
+        <div node_ref=node_ref class="relative h-8 mt-3 bg-gray-800/60 cursor-pointer overflow-hidden">
+            <svg
+                class="absolute size-full text-teal-400 pointer-events-none"
+                viewBox=move || format!("0 -1 {} 2", width.get().max(1.0))
+                preserveAspectRatio="none"
+            >
+                <path d=path stroke="currentColor" stroke-width="1" vector-effect="non-scaling-stroke" />
+            </svg>
+            // Playhead
+            <div
+                class="absolute inset-y-0 w-px bg-blue-300 pointer-events-none"
+                style:left=move || {
+                    let view = view.get();
+                    format!("{}%", 100.0 * view.fraction(frame.get() as f64))
+                }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,37 @@
+use leptos::prelude::window;
+use leptos::wasm_bindgen::JsCast;
+use leptos::web_sys::js_sys::Uint8Array;
+use leptos::web_sys::Response;
+use wasm_bindgen_futures::JsFuture;
+
+/// GET `url` in the browser and return the body as text.
+///
+/// Errors are human-readable: the HTTP status, or whatever the browser threw.
+pub async fn fetch_text(url: &str) -> Result<String, String> {
+    let response = JsFuture::from(window().fetch_with_str(url))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let response: Response = response.unchecked_into();
+    if !response.ok() {
+        return Err(format!("{url}: HTTP {}", response.status()));
+    }
+    let text = JsFuture::from(response.text().map_err(|err| format!("{err:?}"))?)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    Ok(text.as_string().unwrap_or_default())
+}
+
+/// GET `url` in the browser and return the body as bytes.
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = JsFuture::from(window().fetch_with_str(url))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let response: Response = response.unchecked_into();
+    if !response.ok() {
+        return Err(format!("{url}: HTTP {}", response.status()));
+    }
+    let buffer = JsFuture::from(response.array_buffer().map_err(|err| format!("{err:?}"))?)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
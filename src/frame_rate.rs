@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Exact frame rate as `num / den` frames per second, e.g. 30000/1001 for 29.97.
+///
+/// All frame↔time conversions go through this type so x/1001 rates don't drift the way a
+/// rounded `f64` does over long media.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "(u32, u32)", into = "(u32, u32)")]
+pub struct FrameRate {
+    num: u32,
+    den: u32,
+}
+
+/// Tolerance, in frames, for media times that land a hair before a frame boundary.
+const FRAME_EPSILON: f64 = 1e-3;
+
+const fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a
+}
+
+impl FrameRate {
+    pub const FPS_23_976: Self = Self::new(24000, 1001);
+    pub const FPS_24: Self = Self::new(24, 1);
+    pub const FPS_25: Self = Self::new(25, 1);
+    pub const FPS_29_97: Self = Self::new(30000, 1001);
+    pub const FPS_30: Self = Self::new(30, 1);
+    pub const FPS_47_952: Self = Self::new(48000, 1001);
+    pub const FPS_48: Self = Self::new(48, 1);
+    pub const FPS_50: Self = Self::new(50, 1);
+    pub const FPS_59_94: Self = Self::new(60000, 1001);
+    pub const FPS_60: Self = Self::new(60, 1);
+
+    /// The broadcast and cinema rates above, slowest first.
+    pub const STANDARD: [Self; 10] = [
+        Self::FPS_23_976,
+        Self::FPS_24,
+        Self::FPS_25,
+        Self::FPS_29_97,
+        Self::FPS_30,
+        Self::FPS_47_952,
+        Self::FPS_48,
+        Self::FPS_50,
+        Self::FPS_59_94,
+        Self::FPS_60,
+    ];
+
+    /// Reduced `num / den`. Zero components are bumped to 1.
+    pub const fn new(num: u32, den: u32) -> Self {
+        let num = if num == 0 { 1 } else { num };
+        let den = if den == 0 { 1 } else { den };
+        let g = gcd(num, den);
+        Self {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub const fn num(self) -> u32 {
+        self.num
+    }
+
+    pub const fn den(self) -> u32 {
+        self.den
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Integer timecode base: 30 for 29.97, 24 for 23.976 and so on.
+    pub const fn nominal(self) -> u32 {
+        (self.num + self.den / 2) / self.den
+    }
+
+    /// Whether SMPTE counts this rate in drop-frame (29.97 and 59.94).
+    pub const fn is_drop_frame(self) -> bool {
+        self.den == 1001 && (self.num == 30000 || self.num == 60000)
+    }
+
+    /// Zero-based frame displayed at media time `time` seconds.
+    pub fn frame_at(self, time: f64) -> i32 {
+        (time * self.num as f64 / self.den as f64 + FRAME_EPSILON).floor() as i32
+    }
+
+    /// Media time in seconds at which `frame` starts.
+    pub fn time_of(self, frame: i32) -> f64 {
+        frame as f64 * self.den as f64 / self.num as f64
+    }
+
+    /// Media time in seconds to seek to for `frame`: its midpoint rather than its start.
+    ///
+    /// Decoders round seek targets to the nearest presentation timestamp, so aiming at the
+    /// boundary can land on the previous frame when container timestamps are truncated.
+    pub fn seek_time(self, frame: i32) -> f64 {
+        (frame as f64 + 0.5) * self.den as f64 / self.num as f64
+    }
+
+    /// Duration in seconds of one frame.
+    pub fn frame_duration(self) -> f64 {
+        self.den as f64 / self.num as f64
+    }
+
+    /// The standard rate nearest a measured `fps`, if one is within 1%; otherwise `fps`
+    /// itself as converted by `From<f64>`.
+    pub fn snap(fps: f64) -> Self {
+        Self::STANDARD
+            .into_iter()
+            .map(|rate| (rate, (rate.as_f64() - fps).abs() / rate.as_f64()))
+            .filter(|&(_, error)| error < 0.01)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or_else(|| Self::from(fps), |(rate, _)| rate)
+    }
+
+    /// Snapped rate from measured frame durations in seconds.
+    ///
+    /// Durations more than a quarter off the median, such as the jump across a seek or a
+    /// dropped frame, are ignored. The rest are averaged, since timestamps in a millisecond
+    /// timescale alternate around the true duration.
+    pub fn estimate(durations: &[f64]) -> Option<Self> {
+        let mut sorted: Vec<f64> = durations.iter().copied().filter(|d| *d > 0.0).collect();
+        sorted.sort_by(f64::total_cmp);
+        let median = *sorted.get(sorted.len() / 2)?;
+        let typical: Vec<f64> = sorted
+            .into_iter()
+            .filter(|d| (d - median).abs() <= median / 4.0)
+            .collect();
+        let mean = typical.iter().sum::<f64>() / typical.len() as f64;
+        Some(Self::snap(1.0 / mean))
+    }
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::FPS_24
+    }
+}
+
+impl From<f64> for FrameRate {
+    /// Decimal NTSC rates such as 23.976, 29.97 and 59.94 snap to their exact x/1001 form;
+    /// other rates are kept to a thousandth of a frame.
+    fn from(fps: f64) -> Self {
+        if !(fps.is_finite() && fps > 0.0) {
+            return Self::default();
+        }
+        let whole = fps.round();
+        if (fps - whole).abs() < 1e-6 {
+            return Self::new(whole as u32, 1);
+        }
+        let ntsc = (fps * 1.001).round();
+        if (ntsc / 1.001 - fps).abs() < 5e-3 {
+            return Self::new(ntsc as u32 * 1000, 1001);
+        }
+        Self::new((fps * 1000.0).round() as u32, 1000)
+    }
+}
+
+impl From<u32> for FrameRate {
+    fn from(fps: u32) -> Self {
+        Self::new(fps, 1)
+    }
+}
+
+impl From<(u32, u32)> for FrameRate {
+    fn from((num, den): (u32, u32)) -> Self {
+        Self::new(num, den)
+    }
+}
+
+impl From<FrameRate> for (u32, u32) {
+    fn from(rate: FrameRate) -> Self {
+        (rate.num, rate.den)
+    }
+}
+
+impl From<FrameRate> for f64 {
+    fn from(rate: FrameRate) -> Self {
+        rate.as_f64()
+    }
+}
+
+impl fmt::Display for FrameRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            let s = format!("{:.3}", self.as_f64());
+            write!(f, "{}", s.trim_end_matches('0').trim_end_matches('.'))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_f64_snaps_ntsc_rates() {
+        assert_eq!(FrameRate::from(23.976), FrameRate::FPS_23_976);
+        assert_eq!(FrameRate::from(29.97), FrameRate::FPS_29_97);
+        assert_eq!(FrameRate::from(30000.0 / 1001.0), FrameRate::FPS_29_97);
+        assert_eq!(FrameRate::from(59.94), FrameRate::FPS_59_94);
+        assert_eq!(FrameRate::from(25.0), FrameRate::FPS_25);
+        assert_eq!(FrameRate::from(12.5), FrameRate::new(25, 2));
+        assert_eq!(FrameRate::from(f64::NAN), FrameRate::default());
+    }
+
+    #[test]
+    fn no_drift_over_long_media() {
+        // Ten hours at 29.97 is frame 1_078_921; the decimal `29.97` lands a frame early.
+        let rate = FrameRate::FPS_29_97;
+        let frame = 1_078_921;
+        assert_eq!(rate.frame_at(rate.time_of(frame)), frame);
+        assert_eq!(rate.frame_at(36_000.0), 1_078_921);
+    }
+
+    #[test]
+    fn seek_time_round_trips() {
+        for rate in FrameRate::STANDARD {
+            // Every frame of the first minute, then spot checks out to 24 hours.
+            let frames = (0..rate.nominal() as i32 * 60)
+                .chain((1..=24).map(|h| (rate.as_f64() * 3600.0 * h as f64) as i32 - 1));
+            for frame in frames {
+                let time = rate.seek_time(frame);
+                assert_eq!(rate.frame_at(time), frame, "{rate} frame {frame}");
+                assert!(time > rate.time_of(frame) && time < rate.time_of(frame + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn snaps_measured_rates() {
+        assert_eq!(FrameRate::snap(23.98), FrameRate::FPS_23_976);
+        assert_eq!(FrameRate::snap(24.01), FrameRate::FPS_24);
+        assert_eq!(FrameRate::snap(59.7), FrameRate::FPS_59_94);
+        assert_eq!(FrameRate::snap(15.0), FrameRate::new(15, 1));
+    }
+
+    #[test]
+    fn estimates_from_frame_durations() {
+        // 29.97 timestamps rounded to milliseconds, with a seek and a dropped frame mixed in.
+        let times: Vec<f64> = (0..60)
+            .map(|i| (i as f64 * 1001.0 / 30.0).round() / 1000.0)
+            .collect();
+        let mut durations: Vec<f64> = times.windows(2).map(|w| w[1] - w[0]).collect();
+        durations.extend([2.5, 0.067, 0.0]);
+        assert_eq!(FrameRate::estimate(&durations), Some(FrameRate::FPS_29_97));
+        assert_eq!(
+            FrameRate::estimate(&[1001.0 / 24000.0; 8]),
+            Some(FrameRate::FPS_23_976)
+        );
+        assert_eq!(FrameRate::estimate(&[]), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(FrameRate::FPS_25.to_string(), "25");
+        assert_eq!(FrameRate::FPS_29_97.to_string(), "29.97");
+        assert_eq!(FrameRate::FPS_23_976.to_string(), "23.976");
+        assert_eq!(FrameRate::new(25, 2).to_string(), "12.5");
+    }
+}
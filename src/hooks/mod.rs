@@ -0,0 +1,9 @@
+mod use_audio_meter;
+mod use_comments;
+mod use_fps_detection;
+mod use_video_frame_callback;
+
+pub use use_audio_meter::*;
+pub use use_comments::*;
+pub use use_fps_detection::*;
+pub use use_video_frame_callback::*;
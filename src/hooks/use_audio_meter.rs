@@ -0,0 +1,206 @@
+use crate::loudness::{self, ChannelLevels, ShortTermLoudness};
+use leptos::prelude::*;
+use leptos::wasm_bindgen::JsValue;
+use leptos::*;
+use std::time::Duration;
+use web_sys::js_sys::Date;
+use web_sys::{
+    AnalyserNode, AudioContext, AudioNode, BiquadFilterType, ChannelSplitterNode, HtmlMediaElement,
+    MediaElementAudioSourceNode,
+};
+
+/// Most channels metered; enough for 7.1.
+const MAX_CHANNELS: u32 = 8;
+
+/// Samples per analysis block; about 43ms at 48kHz, just under the poll interval.
+const BLOCK: u32 = 2048;
+
+const METER_POLL: Duration = Duration::from_millis(50);
+
+/// Levels of what the player is outputting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meter {
+    /// One entry per channel that has carried sound so far.
+    pub channels: Vec<ChannelLevels>,
+    /// EBU R 128 short-term loudness in LUFS, from Web Audio's approximation of the
+    /// BS.1770 K-weighting filter. `None` until playback starts.
+    pub short_term: Option<f64>,
+}
+
+/// The Web Audio graph: both elements play out through the context, and whichever is
+/// heard also feeds a bank of per-channel analysers.
+struct Graph {
+    context: AudioContext,
+    video: MediaElementAudioSourceNode,
+    audio: Option<MediaElementAudioSourceNode>,
+    splitter: ChannelSplitterNode,
+    /// Plain and K-weighted analysers for each splitter output.
+    analysers: Vec<(AnalyserNode, AnalyserNode)>,
+}
+
+impl Graph {
+    fn new(video: &HtmlMediaElement, audio: Option<&HtmlMediaElement>) -> Result<Self, JsValue> {
+        let context = AudioContext::new()?;
+        let splitter = context.create_channel_splitter_with_number_of_outputs(MAX_CHANNELS)?;
+        let analyser = || -> Result<AnalyserNode, JsValue> {
+            let analyser = context.create_analyser()?;
+            analyser.set_fft_size(BLOCK);
+            Ok(analyser)
+        };
+        let mut analysers = Vec::new();
+        for output in 0..MAX_CHANNELS {
+            let plain = analyser()?;
+            splitter.connect_with_audio_node_and_output(&plain, output)?;
+            // BS.1770 stage one, a high shelf for the head, then the RLB high-pass.
+            let shelf = context.create_biquad_filter()?;
+            shelf.set_type(BiquadFilterType::Highshelf);
+            shelf.frequency().set_value(1681.97);
+            shelf.gain().set_value(4.0);
+            let high_pass = context.create_biquad_filter()?;
+            high_pass.set_type(BiquadFilterType::Highpass);
+            high_pass.frequency().set_value(38.14);
+            high_pass.q().set_value(0.5);
+            let weighted = analyser()?;
+            splitter.connect_with_audio_node_and_output(&shelf, output)?;
+            shelf.connect_with_audio_node(&high_pass)?;
+            high_pass.connect_with_audio_node(&weighted)?;
+            analysers.push((plain, weighted));
+        }
+        // Capture the elements last: once captured they're only heard through the context.
+        Ok(Graph {
+            video: context.create_media_element_source(video)?,
+            audio: audio
+                .map(|audio| context.create_media_element_source(audio))
+                .transpose()?,
+            context,
+            splitter,
+            analysers,
+        })
+    }
+
+    /// Meter `video`, or the separate `audio` element when `separate`.
+    fn route(&self, separate: bool) -> Result<(), JsValue> {
+        let sources = [Some(&self.video), self.audio.as_ref()];
+        for source in sources.iter().flatten() {
+            source.disconnect()?;
+            source.connect_with_audio_node(&self.context.destination())?;
+        }
+        let metered: &AudioNode = match (separate, &self.audio) {
+            (true, Some(audio)) => audio,
+            _ => &self.video,
+        };
+        metered.connect_with_audio_node(&self.splitter)?;
+        Ok(())
+    }
+}
+
+/// Peak, RMS and short-term loudness of the sound from `video`, or from `audio` while
+/// `separate` is set.
+///
+/// The Web Audio graph is built on first `playing`, as browsers won't start audio before
+/// a user gesture and the elements go quiet once routed through a suspended context.
+/// Levels follow the player's volume. Cross-origin media must be served with CORS headers,
+/// or the browser feeds the meters silence.
+pub fn use_audio_meter(
+    video: NodeRef<html::Video>,
+    audio: NodeRef<html::Audio>,
+    separate: Signal<bool>,
+    playing: Signal<bool>,
+) -> Signal<Meter> {
+    let (meter, set_meter) = signal(Meter {
+        channels: Vec::new(),
+        short_term: None,
+    });
+    let graph = StoredValue::new_local(None::<Graph>);
+    let short_term = StoredValue::new_local(ShortTermLoudness::new());
+    let last_poll = StoredValue::new(Date::now());
+
+    Effect::new(move |_| {
+        let separate = separate.get();
+        if !playing.get() {
+            return;
+        }
+        if graph.with_value(Option::is_none) {
+            let Some(video) = video.get_untracked() else {
+                return;
+            };
+            let audio = audio.get_untracked();
+            match Graph::new(&video, audio.as_deref()) {
+                Ok(new) => graph.set_value(Some(new)),
+                Err(err) => {
+                    leptos::logging::warn!("audio meters unavailable: {err:?}");
+                    return;
+                }
+            }
+        }
+        graph.with_value(|graph| {
+            if let Some(graph) = graph {
+                let _ = graph.context.resume();
+                let _ = graph.route(separate);
+            }
+        });
+    });
+
+    let poll = move || {
+        let now = Date::now();
+        let elapsed = (now - last_poll.get_value()) / 1000.0;
+        last_poll.set_value(now);
+        let mut samples = vec![0.0f32; BLOCK as usize];
+        let read = graph.with_value(|graph| {
+            let graph = graph.as_ref()?;
+            Some(
+                graph
+                    .analysers
+                    .iter()
+                    .map(|(plain, weighted)| {
+                        plain.get_float_time_domain_data(&mut samples);
+                        let levels = loudness::levels(&samples);
+                        weighted.get_float_time_domain_data(&mut samples);
+                        (levels, loudness::mean_square(&samples))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+        let Some(read) = read else {
+            return;
+        };
+        set_meter.update(|meter| {
+            let sounding = read
+                .iter()
+                .rposition(|(levels, _)| levels.peak.is_finite())
+                .map_or(0, |last| last + 1);
+            let channels = meter.channels.len().max(sounding).max(1);
+            meter.channels.resize(channels, ChannelLevels::SILENT);
+            for (shown, (levels, _)) in meter.channels.iter_mut().zip(&read) {
+                *shown = ChannelLevels {
+                    peak: loudness::fall(shown.peak, levels.peak, elapsed),
+                    rms: levels.rms,
+                };
+            }
+            if playing.get_untracked() {
+                let mean_squares: Vec<f64> = read[..channels].iter().map(|(_, z)| *z).collect();
+                short_term.update_value(|loudness| loudness.push(elapsed, &mean_squares));
+                meter.short_term = short_term.with_value(ShortTermLoudness::lufs);
+            }
+        });
+    };
+
+    Effect::new(move |_| {
+        let handle = set_interval_with_handle(poll, METER_POLL).ok();
+        on_cleanup(move || {
+            if let Some(handle) = handle {
+                handle.clear();
+            }
+        });
+    });
+
+    on_cleanup(move || {
+        graph.update_value(|graph| {
+            if let Some(graph) = graph.take() {
+                let _ = graph.context.close();
+            }
+        });
+    });
+
+    meter.into()
+}
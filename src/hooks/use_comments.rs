@@ -0,0 +1,95 @@
+use crate::comments::{sort_comments, Comment, CommentStore, NewComment};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use std::rc::Rc;
+
+/// Comments on the current media, shared by the comments panel and the progress bar markers.
+///
+/// Returned by [`use_comments`]; `Copy`, so pass it to as many components as need it.
+#[derive(Clone, Copy)]
+pub struct Comments {
+    store: StoredValue<Rc<dyn CommentStore>, LocalStorage>,
+    media: Signal<String>,
+    list: RwSignal<Vec<Comment>>,
+    error: RwSignal<Option<ServerFnError>>,
+}
+
+impl Comments {
+    /// Comments in timecode order.
+    pub fn list(&self) -> Signal<Vec<Comment>> {
+        self.list.into()
+    }
+
+    /// The last failure talking to the store, cleared by the next success.
+    pub fn error(&self) -> Signal<Option<ServerFnError>> {
+        self.error.into()
+    }
+
+    pub fn reload(&self) {
+        let this = *self;
+        let media = self.media.get_untracked();
+        let store = self.store.get_value();
+        spawn_local(async move {
+            let result = store.list(&media).await;
+            // Drop replies for media that has since been switched away from.
+            if this.media.get_untracked() != media {
+                return;
+            }
+            this.settle(result.map(|mut comments| {
+                sort_comments(&mut comments);
+                this.list.set(comments);
+            }));
+        });
+    }
+
+    pub fn add(&self, comment: NewComment) {
+        let this = *self;
+        let media = self.media.get_untracked();
+        let store = self.store.get_value();
+        spawn_local(async move {
+            let result = store.add(&media, comment).await;
+            this.settle(result.map(|comment| {
+                this.list.update(|list| {
+                    list.push(comment);
+                    sort_comments(list);
+                });
+            }));
+        });
+    }
+
+    pub fn remove(&self, id: u64) {
+        let this = *self;
+        let media = self.media.get_untracked();
+        let store = self.store.get_value();
+        spawn_local(async move {
+            let result = store.remove(&media, id).await;
+            this.settle(result.map(|()| {
+                this.list.update(|list| list.retain(|c| c.id != id));
+            }));
+        });
+    }
+
+    fn settle(&self, result: Result<(), ServerFnError>) {
+        self.error.set(result.err());
+    }
+}
+
+/// Load and edit the comments on `media` through `store`, reloading whenever `media` changes.
+pub fn use_comments<S>(store: S, media: impl Into<Signal<String>>) -> Comments
+where
+    S: CommentStore + 'static,
+{
+    let comments = Comments {
+        store: StoredValue::new_local(Rc::new(store) as Rc<dyn CommentStore>),
+        media: media.into(),
+        list: RwSignal::new(Vec::new()),
+        error: RwSignal::new(None),
+    };
+
+    Effect::new(move |_| {
+        comments.media.track();
+        comments.reload();
+    });
+
+    comments
+}
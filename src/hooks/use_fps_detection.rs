@@ -0,0 +1,107 @@
+use super::use_video_frame_callback::{use_video_frame_callback, VideoFrame};
+use crate::frame_rate::FrameRate;
+use leptos::prelude::*;
+use leptos::*;
+use std::time::Duration;
+use web_sys::js_sys::{Date, Reflect};
+
+/// Consecutive frame durations to collect before settling on a rate.
+const FRAME_SAMPLES: usize = 30;
+
+/// Seconds of playback to count decoded frames over where frame callbacks are missing.
+const QUALITY_WINDOW: f64 = 2.0;
+
+const QUALITY_POLL: Duration = Duration::from_millis(500);
+
+/// Estimate the frame rate of whatever `target` plays, snapped to a standard rate.
+///
+/// Measures the media time between consecutively presented frames where
+/// `requestVideoFrameCallback` is supported, and otherwise counts
+/// `getVideoPlaybackQuality().totalVideoFrames` over a couple of seconds of 1x playback.
+/// Only measures while `enabled`, and starts over when `source` changes. `None` until
+/// there's enough to go on.
+pub fn use_fps_detection(
+    target: NodeRef<html::Video>,
+    enabled: Signal<bool>,
+    source: Signal<String>,
+) -> Signal<Option<FrameRate>> {
+    let (detected, set_detected) = signal(None::<FrameRate>);
+    let durations = StoredValue::new(Vec::<f64>::new());
+    let last_frame = StoredValue::new(None::<VideoFrame>);
+    // Media time, decoded frames and wall-clock milliseconds when a window began.
+    let window_start = StoredValue::new(None::<(f64, u32, f64)>);
+
+    Effect::new(move |_| {
+        source.track();
+        set_detected.set(None);
+        durations.update_value(Vec::clear);
+        last_frame.set_value(None);
+        window_start.set_value(None);
+    });
+
+    let measuring = move || enabled.get_untracked() && detected.get_untracked().is_none();
+
+    let frame_callback_supported = use_video_frame_callback(target, move |frame| {
+        if !measuring() {
+            return;
+        }
+        // Above 1x the browser skips frames, which would read as a lower rate.
+        let normal_speed = target
+            .get_untracked()
+            .is_some_and(|video| video.playback_rate() <= 1.0);
+        if let Some(last) = last_frame.get_value() {
+            if normal_speed && frame.presented_frames == last.presented_frames.wrapping_add(1) {
+                durations.update_value(|d| d.push(frame.media_time - last.media_time));
+            }
+        }
+        last_frame.set_value(Some(frame));
+        if durations.with_value(Vec::len) >= FRAME_SAMPLES {
+            set_detected.set(durations.with_value(|d| FrameRate::estimate(d)));
+        }
+    });
+
+    let sample_quality = move || {
+        if frame_callback_supported.get_untracked() || !measuring() {
+            return;
+        }
+        let Some(video) = target.get_untracked() else {
+            return;
+        };
+        if !Reflect::has(&video, &"getVideoPlaybackQuality".into()).unwrap_or(false) {
+            return;
+        }
+        if video.paused() || video.playback_rate() != 1.0 {
+            window_start.set_value(None);
+            return;
+        }
+        let now = (
+            video.current_time(),
+            video.get_video_playback_quality().total_video_frames(),
+            Date::now(),
+        );
+        let Some((time, frames, wall)) = window_start.get_value() else {
+            window_start.set_value(Some(now));
+            return;
+        };
+        let elapsed = now.0 - time;
+        // A seek or stall during the window throws the count off; start again.
+        if (elapsed - (now.2 - wall) / 1000.0).abs() > 0.1 * elapsed.max(0.1) {
+            window_start.set_value(Some(now));
+            return;
+        }
+        if elapsed >= QUALITY_WINDOW && now.1 > frames {
+            set_detected.set(Some(FrameRate::snap((now.1 - frames) as f64 / elapsed)));
+        }
+    };
+
+    Effect::new(move |_| {
+        let handle = set_interval_with_handle(sample_quality, QUALITY_POLL).ok();
+        on_cleanup(move || {
+            if let Some(handle) = handle {
+                handle.clear();
+            }
+        });
+    });
+
+    detected.into()
+}
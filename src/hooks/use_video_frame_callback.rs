@@ -0,0 +1,123 @@
+use leptos::prelude::*;
+use leptos::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use web_sys::js_sys::{Function, Reflect};
+use web_sys::wasm_bindgen::{prelude::*, JsCast};
+use web_sys::HtmlVideoElement;
+
+#[wasm_bindgen(wasm_bindgen = web_sys::wasm_bindgen)]
+extern "C" {
+    /// `VideoFrameCallbackMetadata`; web-sys has no binding for it yet.
+    #[derive(Debug, Clone)]
+    pub type VideoFrameCallbackMetadata;
+
+    #[wasm_bindgen(method, getter, js_name = mediaTime)]
+    fn media_time(this: &VideoFrameCallbackMetadata) -> f64;
+
+    #[wasm_bindgen(method, getter, js_name = presentedFrames)]
+    fn presented_frames(this: &VideoFrameCallbackMetadata) -> u32;
+
+    #[wasm_bindgen(method, getter, js_name = expectedDisplayTime)]
+    fn expected_display_time(this: &VideoFrameCallbackMetadata) -> f64;
+
+    /// `HTMLVideoElement` with the frame callback methods.
+    #[wasm_bindgen(extends = HtmlVideoElement)]
+    #[derive(Debug, Clone)]
+    type FrameCallbackVideo;
+
+    #[wasm_bindgen(method, js_name = requestVideoFrameCallback)]
+    fn request_video_frame_callback(this: &FrameCallbackVideo, callback: &Function) -> u32;
+
+    #[wasm_bindgen(method, js_name = cancelVideoFrameCallback)]
+    fn cancel_video_frame_callback(this: &FrameCallbackVideo, handle: u32);
+}
+
+/// A frame the compositor has presented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoFrame {
+    /// Presentation timestamp of the frame, in media seconds.
+    pub media_time: f64,
+    /// Frames presented since the element loaded; gaps mean dropped frames.
+    pub presented_frames: u32,
+    /// `performance.now()` time at which the frame is expected to be visible.
+    pub expected_display_time: f64,
+}
+
+type FrameClosure = Closure<dyn FnMut(f64, VideoFrameCallbackMetadata)>;
+
+struct Registration {
+    video: FrameCallbackVideo,
+    handle: Rc<Cell<u32>>,
+    closure: Rc<RefCell<Option<FrameClosure>>>,
+}
+
+/// Call `callback` for every frame presented by `target` via
+/// `HTMLVideoElement.requestVideoFrameCallback`.
+///
+/// Returns whether the browser supports it, so callers can fall back to `timeupdate`.
+pub fn use_video_frame_callback<F>(target: NodeRef<html::Video>, callback: F) -> Signal<bool>
+where
+    F: Fn(VideoFrame) + 'static,
+{
+    let (is_supported, set_is_supported) = signal(false);
+    let callback = Rc::new(callback);
+    let registration = StoredValue::new_local(None::<Registration>);
+
+    let stop = move || {
+        registration.try_update_value(|registration| {
+            if let Some(registration) = registration.take() {
+                registration
+                    .video
+                    .cancel_video_frame_callback(registration.handle.get());
+                registration.closure.borrow_mut().take();
+            }
+        });
+    };
+
+    Effect::new(move |_| {
+        stop();
+        let Some(video) = target.get() else {
+            return;
+        };
+        if !Reflect::has(&video, &"requestVideoFrameCallback".into()).unwrap_or(false) {
+            set_is_supported.set(false);
+            return;
+        }
+        set_is_supported.set(true);
+
+        let video: FrameCallbackVideo = video.unchecked_into();
+        let handle = Rc::new(Cell::new(0));
+        let closure = Rc::new(RefCell::new(None::<FrameClosure>));
+        *closure.borrow_mut() = Some(Closure::new({
+            let video = video.clone();
+            let handle = Rc::clone(&handle);
+            let closure = Rc::clone(&closure);
+            let callback = Rc::clone(&callback);
+            move |_now: f64, metadata: VideoFrameCallbackMetadata| {
+                callback(VideoFrame {
+                    media_time: metadata.media_time(),
+                    presented_frames: metadata.presented_frames(),
+                    expected_display_time: metadata.expected_display_time(),
+                });
+                // The callback is one-shot; re-arm it for the next frame.
+                if let Some(closure) = closure.borrow().as_ref() {
+                    handle
+                        .set(video.request_video_frame_callback(closure.as_ref().unchecked_ref()));
+                }
+            }
+        }));
+        if let Some(first) = closure.borrow().as_ref() {
+            handle.set(video.request_video_frame_callback(first.as_ref().unchecked_ref()));
+        }
+        registration.set_value(Some(Registration {
+            video,
+            handle,
+            closure,
+        }));
+    });
+
+    on_cleanup(stop);
+
+    is_supported.into()
+}
@@ -1,5 +1,28 @@
+pub mod annotation;
 pub mod app;
+pub mod audio;
+pub mod captions;
+pub mod comments;
+pub mod compare;
 pub mod components;
+pub mod fetch;
+pub mod frame_rate;
+pub mod hooks;
+pub mod library;
+pub mod loudness;
+pub mod marker;
+#[cfg(feature = "ssr")]
+pub mod media;
+pub mod pages;
+pub mod paths;
+pub mod peaks;
+pub mod probe;
+#[cfg(any(feature = "ssr", test))]
+pub mod sprites;
+pub mod thumbnail;
+pub mod timecode;
+pub mod timeline;
+pub mod vtt;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -0,0 +1,441 @@
+//! The media library: playable files in the server's media root, paired with their proxies
+//! and described by a probe.
+
+use crate::frame_rate::FrameRate;
+use crate::paths::cache_key;
+use crate::probe::{AudioTrack, Mp4Info};
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Extensions listed in the library.
+pub const MEDIA_EXTENSIONS: [&str; 6] = ["mp4", "m4v", "mov", "webm", "mkv", "ogv"];
+
+/// What a probe could tell about a file; unknown fields are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    /// Seconds.
+    pub duration: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<FrameRate>,
+    /// Video sample format, e.g. `avc1`.
+    pub codec: Option<String>,
+    pub frame_count: Option<u64>,
+    /// Source timecode of the first frame, e.g. `01:00:00:00`.
+    pub start_timecode: Option<String>,
+    pub audio: Vec<AudioTrack>,
+}
+
+impl From<Mp4Info> for MediaInfo {
+    fn from(info: Mp4Info) -> Self {
+        let video = info.video.as_ref();
+        Self {
+            duration: Some(info.duration).filter(|&d| d > 0.0),
+            width: video.map(|v| v.width),
+            height: video.map(|v| v.height),
+            fps: video.map(|v| v.fps),
+            codec: video.map(|v| v.codec.clone()),
+            frame_count: video.map(|v| v.frame_count),
+            start_timecode: info.start_timecode.map(|tc| tc.to_string()),
+            audio: info.audio,
+        }
+    }
+}
+
+/// A file in the library, with the URLs to play it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaEntry {
+    /// URL-safe key for `/watch/:id`, the name encoded with [`cache_key`].
+    pub id: String,
+    /// Path relative to the media root.
+    pub name: String,
+    pub src: String,
+    pub proxy: Option<String>,
+    /// WebVTT sprite track, generated on first request.
+    pub thumbnails: String,
+    /// A single thumbnail image, much cheaper to generate than the track.
+    pub poster: String,
+    pub info: MediaInfo,
+}
+
+impl MediaEntry {
+    pub fn new(name: &str, proxy: Option<&str>, info: MediaInfo) -> Self {
+        Self {
+            id: cache_key(std::path::Path::new(name)),
+            name: name.to_string(),
+            src: format!("/media/{}", encode_path(name)),
+            proxy: proxy.map(|proxy| format!("/media/{}", encode_path(proxy))),
+            thumbnails: format!("/api/thumbnails/{}", encode_path(name)),
+            poster: format!("/api/posters/{}", encode_path(name)),
+            info,
+        }
+    }
+}
+
+pub fn is_media(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, ext)| {
+        MEDIA_EXTENSIONS
+            .iter()
+            .any(|media| ext.eq_ignore_ascii_case(media))
+    })
+}
+
+fn stem(name: &str) -> &str {
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// `stem` without trailing `_`, `-`, `.` and spaces.
+fn base(stem: &str) -> &str {
+    stem.trim_end_matches(['_', '-', '.', ' '])
+}
+
+/// The base stem `name` is a proxy for, if its stem ends in `proxy`.
+fn proxy_source(name: &str) -> Option<&str> {
+    let stem = stem(name);
+    let source = stem
+        .len()
+        .checked_sub(5)
+        .filter(|&i| stem.is_char_boundary(i) && stem[i..].eq_ignore_ascii_case("proxy"))
+        .map(|i| base(&stem[..i]))?;
+    (!source.is_empty()).then_some(source)
+}
+
+/// Pair each file with its proxy: a file whose stem is the same plus a `proxy` suffix, such
+/// as `clip_proxy.mp4` for `clip.mp4` or `clip_.mp4`. Proxies aren't listed on their own.
+///
+/// Returns `(name, proxy)` sorted by name.
+pub fn pair_proxies(names: &[String]) -> Vec<(String, Option<String>)> {
+    let mut entries: Vec<_> = names
+        .iter()
+        .filter(|name| proxy_source(name).is_none())
+        .map(|name| {
+            let proxy = names
+                .iter()
+                .find(|other| proxy_source(other) == Some(base(stem(name))))
+                .cloned();
+            (name.clone(), proxy)
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour up.
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Percent-encode a relative path for a URL, keeping the `/` separators.
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Reads [`MediaInfo`] from a file on the server.
+#[cfg(feature = "ssr")]
+pub trait MediaProbe: Send + Sync {
+    fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String>;
+}
+
+/// Reads MP4 and MOV headers with [`crate::probe`].
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mp4Probe;
+
+#[cfg(feature = "ssr")]
+impl MediaProbe for Mp4Probe {
+    fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String> {
+        let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+        crate::probe::probe(std::io::BufReader::new(file))
+            .map(MediaInfo::from)
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Tries the first probe, then the second if that fails.
+#[cfg(feature = "ssr")]
+impl<A: MediaProbe, B: MediaProbe> MediaProbe for (A, B) {
+    fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String> {
+        self.0.probe(path).or_else(|_| self.1.probe(path))
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl MediaProbe for crate::sprites::FfmpegDecoder {
+    fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String> {
+        let out = crate::sprites::run(
+            std::process::Command::new(&self.ffprobe)
+                .args(["-v", "error", "-select_streams", "v:0", "-show_entries"])
+                .arg("stream=width,height,avg_frame_rate,r_frame_rate:format=duration")
+                .args(["-of", "json"])
+                .arg(path),
+        )
+        .map_err(|err| err.to_string())?;
+        let json: serde_json::Value =
+            serde_json::from_slice(&out).map_err(|err| err.to_string())?;
+        let stream = &json["streams"][0];
+        let dimension = |key: &str| stream[key].as_u64().map(|n| n as u32);
+        let rate = |key: &str| {
+            let (num, den) = stream[key].as_str()?.split_once('/')?;
+            let (num, den) = (num.parse().ok()?, den.parse().ok()?);
+            (num > 0 && den > 0).then(|| FrameRate::new(num, den))
+        };
+        Ok(MediaInfo {
+            duration: json["format"]["duration"]
+                .as_str()
+                .and_then(|d| d.parse().ok()),
+            width: dimension("width"),
+            height: dimension("height"),
+            fps: rate("avg_frame_rate").or_else(|| rate("r_frame_rate")),
+            ..MediaInfo::default()
+        })
+    }
+}
+
+/// Remembers what `P` found for each file until the file is modified, so listing the
+/// library only probes what's new or changed.
+#[cfg(feature = "ssr")]
+pub struct CachedProbe<P> {
+    probe: P,
+    cache: std::sync::Mutex<std::collections::HashMap<std::path::PathBuf, Probed>>,
+}
+
+/// A file's modification time when probed, and what the probe made of it.
+#[cfg(feature = "ssr")]
+type Probed = (std::time::SystemTime, Result<MediaInfo, String>);
+
+#[cfg(feature = "ssr")]
+impl<P: MediaProbe> CachedProbe<P> {
+    pub fn new(probe: P) -> Self {
+        Self {
+            probe,
+            cache: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl<P: MediaProbe> MediaProbe for CachedProbe<P> {
+    fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String> {
+        use std::sync::PoisonError;
+
+        let modified = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .map_err(|err| err.to_string())?;
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(path)
+            .cloned();
+        if let Some((_, result)) = cached.filter(|(when, _)| *when == modified) {
+            return result;
+        }
+        // Probed without holding the lock, so one slow file doesn't stall the others.
+        let result = self.probe.probe(path);
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(path.to_path_buf(), (modified, result.clone()));
+        result
+    }
+}
+
+/// The media root and how to probe it, provided as context by `main.rs`.
+#[cfg(feature = "ssr")]
+#[derive(Clone)]
+pub struct Library {
+    pub root: std::path::PathBuf,
+    pub probe: std::sync::Arc<dyn MediaProbe>,
+}
+
+#[cfg(feature = "ssr")]
+impl Library {
+    /// Every media file directly in the root. Files the probe can't read are still listed,
+    /// without details.
+    pub fn scan(&self) -> std::io::Result<Vec<MediaEntry>> {
+        Ok(pair_proxies(&self.names()?)
+            .into_iter()
+            .map(|(name, proxy)| self.entry(&name, proxy.as_deref()))
+            .collect())
+    }
+
+    /// The file `id` stands for, probing only that one, or `None` if it isn't listed.
+    pub fn get(&self, id: &str) -> std::io::Result<Option<MediaEntry>> {
+        Ok(pair_proxies(&self.names()?)
+            .into_iter()
+            .find(|(name, _)| cache_key(std::path::Path::new(name)) == id)
+            .map(|(name, proxy)| self.entry(&name, proxy.as_deref())))
+    }
+
+    /// Names of the media files directly in the root.
+    fn names(&self) -> std::io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str().filter(|name| is_media(name)) {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn entry(&self, name: &str, proxy: Option<&str>) -> MediaEntry {
+        let info = self.probe.probe(&self.root.join(name)).unwrap_or_default();
+        MediaEntry::new(name, proxy, info)
+    }
+}
+
+/// Run `f` on the [`Library`] in context, off the async runtime.
+#[cfg(feature = "ssr")]
+async fn with_library<T: Send + 'static>(
+    f: impl FnOnce(Library) -> std::io::Result<T> + Send + 'static,
+) -> Result<T, ServerFnError> {
+    let library = use_context::<Library>()
+        .ok_or_else(|| ServerFnError::new("no media library provided to server functions"))?;
+    tokio::task::spawn_blocking(move || f(library))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?
+        .map_err(|err| ServerFnError::new(err.to_string()))
+}
+
+#[server]
+pub async fn list_media() -> Result<Vec<MediaEntry>, ServerFnError> {
+    with_library(|library| library.scan()).await
+}
+
+#[server]
+pub async fn get_media(id: String) -> Result<MediaEntry, ServerFnError> {
+    let entry = with_library({
+        let id = id.clone();
+        move |library| library.get(&id)
+    })
+    .await?;
+    entry.ok_or_else(|| ServerFnError::new(format!("no media {id}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn pairs_proxies() {
+        let entries = pair_proxies(&names(&[
+            "Metallborne3_proxy.mp4",
+            "b.mov",
+            "Metallborne3_.mp4",
+            "a.mp4",
+            "a-proxy.webm",
+        ]));
+        assert_eq!(
+            entries,
+            [
+                (
+                    "Metallborne3_.mp4".into(),
+                    Some("Metallborne3_proxy.mp4".into())
+                ),
+                ("a.mp4".into(), Some("a-proxy.webm".into())),
+                ("b.mov".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn urls() {
+        let entry = MediaEntry::new("My Clip #1.mp4", None, MediaInfo::default());
+        assert_eq!(entry.id, "My_20Clip_20_231.mp4");
+        let other = MediaEntry::new("my-clip-1.mp4", None, MediaInfo::default());
+        assert_ne!(other.id, entry.id);
+        assert_eq!(entry.src, "/media/My%20Clip%20%231.mp4");
+        assert_eq!(entry.thumbnails, "/api/thumbnails/My%20Clip%20%231.mp4");
+        assert_eq!(entry.poster, "/api/posters/My%20Clip%20%231.mp4");
+        assert!(is_media("x.MOV"));
+        assert!(!is_media("notes.txt"));
+        assert_eq!(format_duration(83.4), "1:23");
+        assert_eq!(format_duration(3725.0), "1:02:05");
+    }
+
+    /// Counts probes, reporting each file's length as its duration.
+    #[cfg(feature = "ssr")]
+    #[derive(Default)]
+    struct CountingProbe(std::sync::atomic::AtomicUsize);
+
+    #[cfg(feature = "ssr")]
+    impl MediaProbe for CountingProbe {
+        fn probe(&self, path: &std::path::Path) -> Result<MediaInfo, String> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let len = std::fs::metadata(path)
+                .map_err(|err| err.to_string())?
+                .len();
+            Ok(MediaInfo {
+                duration: Some(len as f64),
+                ..MediaInfo::default()
+            })
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn probes_only_what_changed() {
+        use std::sync::Arc;
+
+        let root = std::env::temp_dir().join(format!("library-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for name in ["My Clip.mp4", "my-clip.mp4", "my-clip_proxy.mp4"] {
+            std::fs::write(root.join(name), b"x").unwrap();
+        }
+        let probe = Arc::new(CachedProbe::new(CountingProbe::default()));
+        let library = Library {
+            root: root.clone(),
+            probe: probe.clone(),
+        };
+        let count = || probe.probe.0.load(std::sync::atomic::Ordering::SeqCst);
+
+        let entries = library.scan().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[0].id, entries[1].id);
+        assert_eq!(count(), 2);
+        library.scan().unwrap();
+        assert_eq!(count(), 2);
+
+        let entry = library.get("my-clip.mp4").unwrap().unwrap();
+        assert_eq!(entry.proxy.as_deref(), Some("/media/my-clip_proxy.mp4"));
+        assert!(library.get("missing.mp4").unwrap().is_none());
+        assert_eq!(count(), 2);
+
+        // A rewritten file is probed again.
+        std::fs::write(root.join("my-clip.mp4"), b"xyz").unwrap();
+        std::fs::File::options()
+            .append(true)
+            .open(root.join("my-clip.mp4"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))
+            .unwrap();
+        let entry = library.get("my-clip.mp4").unwrap().unwrap();
+        assert_eq!(entry.info.duration, Some(3.0));
+        assert_eq!(count(), 3);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+/// Length in seconds of the EBU R 128 short-term loudness window.
+pub const SHORT_TERM_WINDOW: f64 = 3.0;
+
+/// How fast, in dB per second, a meter falls back after a louder block.
+pub const FALL_RATE: f64 = 20.0;
+
+/// Peak and RMS level of one channel over a block of samples, in dBFS.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelLevels {
+    pub peak: f64,
+    pub rms: f64,
+}
+
+impl ChannelLevels {
+    pub const SILENT: Self = ChannelLevels {
+        peak: f64::NEG_INFINITY,
+        rms: f64::NEG_INFINITY,
+    };
+}
+
+/// Decibels relative to full scale of a linear `amplitude`; minus infinity for silence.
+pub fn dbfs(amplitude: f64) -> f64 {
+    20.0 * amplitude.log10()
+}
+
+pub fn mean_square(samples: &[f32]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / samples.len() as f64
+}
+
+pub fn levels(samples: &[f32]) -> ChannelLevels {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    ChannelLevels {
+        peak: dbfs(peak as f64),
+        rms: dbfs(mean_square(samples).sqrt()),
+    }
+}
+
+/// BS.1770 weight of `channel` in a `channels`-channel mix, assuming SMPTE order
+/// (L R C LFE Ls Rs) for 5.1 and up: the LFE doesn't count and surrounds count extra.
+pub fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match channel {
+        3 if channels >= 6 => 0.0,
+        4 | 5 if channels >= 6 => 1.41,
+        _ => 1.0,
+    }
+}
+
+/// A level meter's reading after `elapsed` seconds: `level` if it's louder than `shown`,
+/// otherwise `shown` fallen by [`FALL_RATE`] so short peaks stay readable.
+pub fn fall(shown: f64, level: f64, elapsed: f64) -> f64 {
+    level.max(shown - FALL_RATE * elapsed)
+}
+
+/// Short-term loudness in LUFS over the last [`SHORT_TERM_WINDOW`] seconds.
+///
+/// Fed with the mean square of each channel after K-weighting, block by block. Blocks may
+/// be any length; each counts in proportion to its duration.
+#[derive(Debug, Clone, Default)]
+pub struct ShortTermLoudness {
+    /// Duration and weighted power of each block in the window, oldest first.
+    blocks: VecDeque<(f64, f64)>,
+    duration: f64,
+}
+
+impl ShortTermLoudness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block `duration` seconds long with K-weighted `mean_squares`, one per channel.
+    pub fn push(&mut self, duration: f64, mean_squares: &[f64]) {
+        if duration <= 0.0 {
+            return;
+        }
+        let power = mean_squares
+            .iter()
+            .enumerate()
+            .map(|(i, z)| channel_weight(i, mean_squares.len()) * z)
+            .sum::<f64>();
+        self.blocks.push_back((duration, power));
+        self.duration += duration;
+        while let Some(&(oldest, _)) = self.blocks.front() {
+            if self.duration - oldest < SHORT_TERM_WINDOW {
+                break;
+            }
+            self.duration -= oldest;
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Loudness of the window; `None` before the first block, minus infinity for silence.
+    pub fn lufs(&self) -> Option<f64> {
+        if self.blocks.is_empty() {
+            return None;
+        }
+        let energy = self.blocks.iter().map(|(d, p)| d * p).sum::<f64>();
+        Some(-0.691 + 10.0 * (energy / self.duration).log10())
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.duration = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn sine_levels() {
+        let sine: Vec<f32> = (0..48_000)
+            .map(|i| (i as f32 * 1000.0 * std::f32::consts::TAU / 48_000.0).sin() * 0.5)
+            .collect();
+        let levels = levels(&sine);
+        assert!(close(levels.peak, -6.02), "{levels:?}");
+        assert!(close(levels.rms, -9.03), "{levels:?}");
+        assert_eq!(super::levels(&[0.0; 16]), ChannelLevels::SILENT);
+        assert_eq!(fall(-10.0, -30.0, 0.5), -20.0);
+        assert_eq!(fall(-10.0, -5.0, 0.5), -5.0);
+    }
+
+    #[test]
+    fn short_term_loudness() {
+        let mut loudness = ShortTermLoudness::new();
+        assert_eq!(loudness.lufs(), None);
+        // A full-scale sine after K-weighting, in one channel then in both.
+        loudness.push(1.0, &[0.5]);
+        assert!(close(loudness.lufs().unwrap(), -3.70));
+        loudness.clear();
+        loudness.push(1.0, &[0.5, 0.5]);
+        assert!(close(loudness.lufs().unwrap(), -0.69));
+
+        // The LFE is ignored; after three seconds of silence the tone has left the window.
+        let mut loudness = ShortTermLoudness::new();
+        loudness.push(1.0, &[0.0, 0.0, 0.0, 0.5, 0.0, 0.0]);
+        assert_eq!(loudness.lufs(), Some(f64::NEG_INFINITY));
+        loudness.push(1.0, &[0.5, 0.0]);
+        loudness.push(2.0, &[0.0, 0.0]);
+        assert!(close(loudness.lufs().unwrap(), -3.70 - 10.0 * 3f64.log10()));
+        loudness.push(1.0, &[0.0, 0.0]);
+        assert_eq!(loudness.lufs(), Some(f64::NEG_INFINITY));
+    }
+}
@@ -1,4 +1,3 @@
-
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
@@ -7,18 +6,65 @@ async fn main() {
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use leptos_video::app::*;
+    use leptos_video::comments::MemoryCommentStore;
+    use leptos_video::library::{CachedProbe, Library, Mp4Probe};
+    use leptos_video::media;
+    use leptos_video::peaks::{self, PeaksCache, DEFAULT_SAMPLES_PER_PIXEL};
+    use leptos_video::sprites::{self, FfmpegDecoder, SpriteCache, SpriteOptions};
+    use std::path::PathBuf;
+    use std::sync::Arc;
 
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
     let leptos_options = conf.leptos_options;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
+    // Review comments live for as long as the server does.
+    let comment_store = MemoryCommentStore::new();
+    // Media is served under /media from MEDIA_ROOT, the site root by default. Sprite sheets
+    // generated for it are served from the site root, library posters from /api/posters and
+    // waveform peaks from /api/peaks.
+    let site_root = PathBuf::from(&*leptos_options.site_root);
+    let media_root =
+        std::env::var_os("MEDIA_ROOT").map_or_else(|| site_root.clone(), PathBuf::from);
+    let waveforms = PeaksCache {
+        cache_root: site_root.clone(),
+        media_root: media_root.clone(),
+        samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+    };
+    let thumbnails = SpriteCache {
+        site_root,
+        media_root: media_root.clone(),
+        options: SpriteOptions::default(),
+    };
+    let library = Library {
+        root: media_root.clone(),
+        probe: Arc::new(CachedProbe::new((Mp4Probe, FfmpegDecoder::default()))),
+    };
 
     let app = Router::new()
-        .leptos_routes(&leptos_options, routes, {
-            let leptos_options = leptos_options.clone();
-            move || shell(leptos_options.clone())
-        })
+        .nest_service("/media", media::router(media_root))
+        .nest_service(
+            "/api/thumbnails",
+            sprites::service(thumbnails.clone(), FfmpegDecoder::default()),
+        )
+        .nest_service(
+            "/api/posters",
+            sprites::poster_service(thumbnails, FfmpegDecoder::default()),
+        )
+        .nest_service("/api/peaks", peaks::service(waveforms))
+        .leptos_routes_with_context(
+            &leptos_options,
+            routes,
+            move || {
+                provide_context(comment_store.clone());
+                provide_context(library.clone());
+            },
+            {
+                let leptos_options = leptos_options.clone();
+                move || shell(leptos_options.clone())
+            },
+        )
         .fallback(leptos_axum::file_and_error_handler(shell))
         .with_state(leptos_options);
 
@@ -0,0 +1,144 @@
+use crate::frame_rate::FrameRate;
+use crate::vtt::{self, VttError};
+use serde::{Deserialize, Serialize};
+
+/// A point or span on the timeline, such as a chapter, shot or note.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Marker {
+    /// Zero-based media frame the marker starts on.
+    pub frame: i32,
+    /// Last frame of a span; `None` for a single-frame tick.
+    #[serde(default)]
+    pub end_frame: Option<i32>,
+    #[serde(default)]
+    pub label: String,
+    /// CSS color; markers without one use [`DEFAULT_COLOR`].
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+pub const DEFAULT_COLOR: &str = "#f59e0b";
+
+impl Marker {
+    pub fn color(&self) -> &str {
+        self.color.as_deref().unwrap_or(DEFAULT_COLOR)
+    }
+
+    pub fn last_frame(&self) -> i32 {
+        self.end_frame.unwrap_or(self.frame).max(self.frame)
+    }
+
+    pub fn contains(&self, frame: i32) -> bool {
+        (self.frame..=self.last_frame()).contains(&frame)
+    }
+}
+
+/// Markers from a JSON sidecar: an array of [`Marker`] objects, only `frame` required.
+pub fn from_json(json: &str) -> Result<Vec<Marker>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// One span marker per cue of a WebVTT chapters file, labelled with the cue text.
+pub fn from_vtt_chapters(text: &str, rate: FrameRate) -> Result<Vec<Marker>, VttError> {
+    Ok(vtt::parse(text)?
+        .into_iter()
+        .map(|cue| {
+            let frame = rate.frame_at(cue.start);
+            Marker {
+                frame,
+                end_frame: Some((rate.frame_at(cue.end) - 1).max(frame)),
+                label: cue.text,
+                color: None,
+                category: Some("chapter".to_string()),
+            }
+        })
+        .collect())
+}
+
+/// Start of the first marker after `frame`.
+pub fn next_marker(markers: &[Marker], frame: i32) -> Option<i32> {
+    markers.iter().map(|m| m.frame).filter(|&f| f > frame).min()
+}
+
+/// Start of the last marker before `frame`.
+pub fn prev_marker(markers: &[Marker], frame: i32) -> Option<i32> {
+    markers.iter().map(|m| m.frame).filter(|&f| f < frame).max()
+}
+
+/// The marker edge nearest `frame` if within `threshold` frames, else `frame`.
+pub fn snap(markers: &[Marker], frame: i32, threshold: i32) -> i32 {
+    markers
+        .iter()
+        .flat_map(|m| [m.frame, m.last_frame()])
+        .filter(|edge| edge.abs_diff(frame) <= threshold.unsigned_abs())
+        .min_by_key(|edge| edge.abs_diff(frame))
+        .unwrap_or(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(frame: i32) -> Marker {
+        Marker {
+            frame,
+            end_frame: None,
+            label: String::new(),
+            color: None,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn navigation() {
+        let markers = [tick(100), tick(10), tick(50)];
+        assert_eq!(next_marker(&markers, 10), Some(50));
+        assert_eq!(next_marker(&markers, 100), None);
+        assert_eq!(prev_marker(&markers, 50), Some(10));
+        assert_eq!(prev_marker(&markers, 10), None);
+    }
+
+    #[test]
+    fn snaps_to_nearest_edge() {
+        let span = Marker {
+            end_frame: Some(80),
+            ..tick(60)
+        };
+        let markers = [tick(10), span];
+        assert_eq!(snap(&markers, 13, 5), 10);
+        assert_eq!(snap(&markers, 20, 5), 20);
+        assert_eq!(snap(&markers, 78, 5), 80);
+    }
+
+    #[test]
+    fn snaps_at_the_ends_of_the_range() {
+        let markers = [tick(i32::MIN), tick(i32::MAX)];
+        assert_eq!(snap(&markers, i32::MIN + 3, 5), i32::MIN);
+        assert_eq!(snap(&markers, 0, 5), 0);
+        assert_eq!(snap(&markers, i32::MAX - 3, 5), i32::MAX);
+    }
+
+    #[test]
+    fn json_sidecar() {
+        let markers = from_json(
+            r##"[{"frame": 12, "label": "VFX", "color": "#f00"}, {"frame": 40, "end_frame": 60}]"##,
+        )
+        .unwrap();
+        assert_eq!(markers[0].color(), "#f00");
+        assert_eq!(markers[1].color(), DEFAULT_COLOR);
+        assert!(markers[1].contains(60));
+    }
+
+    #[test]
+    fn vtt_chapters() {
+        let text =
+            "WEBVTT\n\n00:00.000 --> 00:02.000\nOpening\n\n00:02.000 --> 00:05.000\nTitles\n";
+        let markers = from_vtt_chapters(text, FrameRate::FPS_25).unwrap();
+        assert_eq!(markers.len(), 2);
+        assert_eq!((markers[0].frame, markers[0].end_frame), (0, Some(49)));
+        assert_eq!((markers[1].frame, markers[1].end_frame), (50, Some(124)));
+        assert_eq!(markers[1].label, "Titles");
+    }
+}
@@ -0,0 +1,189 @@
+//! Media file serving with byte ranges, so the browser can seek long files without
+//! downloading them first.
+
+use axum::body::Body;
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+/// Media changes rarely but can be replaced in place, so let caches keep it briefly and
+/// revalidate with the ETag after that.
+const CACHE_CONTROL: &str = "public, max-age=3600, must-revalidate";
+
+/// A `Range` header resolved against a file's length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No usable range; send the whole file.
+    Full,
+    /// Inclusive byte offsets.
+    Partial(u64, u64),
+    /// Well-formed but outside the file: 416.
+    Unsatisfiable,
+}
+
+/// Resolve a `Range` header value against a file of `len` bytes.
+///
+/// Only a single `bytes` range is honored. Headers in other units, with several ranges or
+/// that don't parse are ignored, which HTTP allows, and the whole file is sent.
+pub fn parse_range(value: &str, len: u64) -> ByteRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    if first.is_empty() {
+        // Suffix range: the last `n` bytes.
+        return match last.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(n) => ByteRange::Partial(len.saturating_sub(n), len - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(first) = first.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let last = match last {
+        "" => u64::MAX,
+        last => match last.parse::<u64>() {
+            Ok(last) if last >= first => last,
+            _ => return ByteRange::Full,
+        },
+    };
+    if first >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(first, last.min(len - 1))
+}
+
+/// Router serving files under `root` at `/{*path}`; nest it under a prefix such as `/media`.
+///
+/// Responses carry `Accept-Ranges`, an `ETag` and `Last-Modified`, and honor `Range`,
+/// `If-Range`, `If-None-Match` and `If-Modified-Since`.
+pub fn router(root: impl Into<PathBuf>) -> Router {
+    Router::new()
+        .route("/{*path}", get(serve))
+        .with_state(root.into())
+}
+
+async fn serve(
+    State(root): State<PathBuf>,
+    UrlPath(path): UrlPath<String>,
+    method: Method,
+    headers: HeaderMap,
+) -> Response {
+    let Some(relative) = crate::paths::safe_relative(&path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let path = root.join(relative);
+    let Ok(metadata) = tokio::fs::metadata(&path).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !metadata.is_file() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let len = metadata.len();
+    let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+    let etag = etag(len, modified);
+    let last_modified = httpdate::fmt_http_date(modified);
+
+    let header_str = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+    };
+    let not_modified = match header_str(header::IF_NONE_MATCH) {
+        Some(tags) => tags
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag),
+        None => header_str(header::IF_MODIFIED_SINCE)
+            .and_then(|since| httpdate::parse_http_date(since).ok())
+            .is_some_and(|since| truncate_secs(modified) <= since),
+    };
+
+    let mut response = Response::builder()
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(header::LAST_MODIFIED, &last_modified)
+        .header(header::CACHE_CONTROL, CACHE_CONTROL);
+    if not_modified {
+        return response
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    // A stale `If-Range` means the client's partial copy is outdated: send everything.
+    let range_applies = header_str(header::IF_RANGE)
+        .is_none_or(|validator| validator == etag || validator == last_modified);
+    let range = match header_str(header::RANGE) {
+        Some(value) if range_applies => parse_range(value, len),
+        _ => ByteRange::Full,
+    };
+    let (start, end) = match range {
+        ByteRange::Full => {
+            response = response.status(StatusCode::OK);
+            (0, len.saturating_sub(1))
+        }
+        ByteRange::Partial(start, end) => {
+            response = response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"));
+            (start, end)
+        }
+        ByteRange::Unsatisfiable => {
+            return response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                .body(Body::empty())
+                .unwrap();
+        }
+    };
+    let count = if len == 0 { 0 } else { end - start + 1 };
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    response = response
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(header::CONTENT_LENGTH, count);
+    if method == Method::HEAD {
+        return response.body(Body::empty()).unwrap();
+    }
+
+    let mut file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
+    if let Err(err) = file.seek(std::io::SeekFrom::Start(start)).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+    }
+    response
+        .body(Body::from_stream(ReaderStream::new(file.take(count))))
+        .unwrap()
+}
+
+/// Strong validator from the file's size and modification time.
+fn etag(len: u64, modified: SystemTime) -> String {
+    let nanos = modified
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos());
+    format!("\"{len:x}-{nanos:x}\"")
+}
+
+/// HTTP dates have whole seconds, so compare against the mtime rounded down.
+fn truncate_secs(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    UNIX_EPOCH + std::time::Duration::from_secs(secs)
+}
@@ -0,0 +1,82 @@
+use crate::library::{format_duration, list_media, MediaEntry};
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Width of a library card's thumbnail in pixels.
+const CARD_WIDTH: f64 = 160.0;
+
+/// Every file in the media root, linking to its watch page.
+#[component]
+pub fn LibraryPage() -> impl IntoView {
+    let media = Resource::new(|| (), |_| list_media());
+
+    view! {
+        <h1 class="text-xl p-2">"Library"</h1>
+        <Suspense fallback=|| view! { <p class="p-2">"Loading…"</p> }>
+            {move || {
+                media
+                    .get()
+                    .map(|result| match result {
+                        Ok(entries) if entries.is_empty() => {
+                            view! { <p class="p-2">"No media found."</p> }.into_any()
+                        }
+                        Ok(entries) => {
+                            view! {
+                                <ul class="flex flex-wrap gap-4 p-2">
+                                    {entries
+                                        .into_iter()
+                                        .map(|entry| view! { <MediaCard entry /> })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                        Err(err) => view! { <p class="p-2 text-red-500">{err.to_string()}</p> }.into_any(),
+                    })
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn MediaCard(entry: MediaEntry) -> impl IntoView {
+    // Posters are generated on first request, so cards only ask for them once scrolled
+    // into view, and hide them if they can't be made.
+    let (has_poster, set_has_poster) = signal(true);
+    let info = entry.info;
+    let details = [
+        info.duration.map(format_duration),
+        info.width.zip(info.height).map(|(w, h)| format!("{w}×{h}")),
+        info.fps.map(|fps| format!("{fps} fps")),
+        info.codec,
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+
+    view! {
+        <li style:width=format!("{CARD_WIDTH}px")>
+            <A href=format!("/watch/{}", entry.id)>
+                <div
+                    class="bg-black flex items-center justify-center overflow-hidden"
+                    style:height=format!("{}px", CARD_WIDTH * 9.0 / 16.0)
+                >
+                    <Show when=move || has_poster.get()>
+                        <img
+                            src=entry.poster.clone()
+                            loading="lazy"
+                            alt=""
+                            class="size-full object-contain"
+                            on:error=move |_| set_has_poster.set(false)
+                        />
+                    </Show>
+                </div>
+                <div class="text-sm truncate" title=entry.name.clone()>
+                    {entry.name.clone()}
+                </div>
+                <div class="text-xs text-gray-500">{details}</div>
+            </A>
+        </li>
+    }
+}
@@ -0,0 +1,69 @@
+use crate::audio::AudioSource;
+use crate::comments::ServerCommentStore;
+use crate::components::comments_panel::CommentsPanel;
+use crate::components::video::Video;
+use crate::components::video_controller::VideoController;
+use crate::hooks::use_comments;
+use crate::library::{get_media, MediaEntry};
+use leptos::prelude::*;
+use leptos_router::components::A;
+use leptos_router::hooks::use_params_map;
+
+/// The player for the library entry named by the `:id` route parameter.
+#[component]
+pub fn WatchPage() -> impl IntoView {
+    let params = use_params_map();
+    let id = move || params.read().get("id").unwrap_or_default();
+    let media = Resource::new(id, get_media);
+
+    view! {
+        <div class="p-2">
+            <A href="/library">"← Library"</A>
+        </div>
+        <Suspense fallback=|| view! { <p class="p-2">"Loading…"</p> }>
+            {move || {
+                media
+                    .get()
+                    .map(|result| match result {
+                        Ok(entry) => view! { <Player entry /> }.into_any(),
+                        Err(err) => view! { <p class="p-2 text-red-500">{err.to_string()}</p> }.into_any(),
+                    })
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn Player(entry: MediaEntry) -> impl IntoView {
+    let controller = VideoController::new();
+    let annotations = RwSignal::new(Vec::new());
+    let comments = use_comments(ServerCommentStore, entry.name.clone());
+    // Probed labels beat the browser's, which are often blank for QuickTime files.
+    let audio_tracks = if entry.info.audio.len() > 1 {
+        entry.info.audio.iter().map(AudioSource::from).collect()
+    } else {
+        Vec::new()
+    };
+
+    view! {
+        <h1 class="text-xl px-2">{entry.name.clone()}</h1>
+        <div class="flex">
+            <div style="width:800px; height:400px;">
+                <Video
+                    src=entry.src
+                    proxy=entry.proxy.unwrap_or_default()
+                    fps=entry.info.fps
+                    start_timecode=entry.info.start_timecode
+                    controller=controller
+                    comments=comments
+                    annotations=annotations
+                    thumbnails=entry.thumbnails
+                    audio_tracks
+                />
+            </div>
+            <div style="width:320px; height:400px;">
+                <CommentsPanel comments controller />
+            </div>
+        </div>
+    }
+}
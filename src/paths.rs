@@ -0,0 +1,101 @@
+//! Mapping request paths onto the media root, and media onto the server-side caches
+//! derived from it.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// `media` as a relative path with no `..`, or `None` if it would escape the root.
+pub fn safe_relative(media: &str) -> Option<PathBuf> {
+    let path = Path::new(media.trim_start_matches('/'));
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!clean.as_os_str().is_empty()).then_some(clean)
+}
+
+/// A file name or URL segment standing for `relative`, distinct for every path: letters,
+/// digits, `.` and `-` are kept and every other byte, `_` included, is written `_XX` in hex.
+pub fn cache_key(relative: &Path) -> String {
+    let mut key = String::new();
+    for byte in relative.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'-' {
+            key.push(byte as char);
+        } else {
+            key.push_str(&format!("_{byte:02X}"));
+        }
+    }
+    key
+}
+
+/// One lock per cache key, so concurrent requests for the same media wait on a single
+/// generation rather than each running their own and racing on the files.
+#[derive(Debug, Clone, Default)]
+pub struct KeyLocks(Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>);
+
+impl KeyLocks {
+    /// Run `f` holding the lock for `key`.
+    pub fn with<T>(&self, key: &str, f: impl FnOnce() -> T) -> T {
+        let lock = {
+            let mut locks = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            // Forget locks nobody holds or waits on.
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            Arc::clone(locks.entry(key.to_string()).or_default())
+        };
+        let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+        f()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_escaping_paths() {
+        assert_eq!(safe_relative("a/b.mp4"), Some(PathBuf::from("a/b.mp4")));
+        assert_eq!(safe_relative("/a.mp4"), Some(PathBuf::from("a.mp4")));
+        assert_eq!(safe_relative("../secret"), None);
+        assert_eq!(safe_relative("a/../../b"), None);
+        assert_eq!(safe_relative(""), None);
+    }
+
+    #[test]
+    fn cache_keys_are_distinct() {
+        let keys: Vec<String> = ["a/b.mp4", "a_b.mp4", "clip one.mp4", "clip_one.mp4"]
+            .iter()
+            .map(|path| cache_key(Path::new(path)))
+            .collect();
+        assert_eq!(
+            keys,
+            ["a_2Fb.mp4", "a_5Fb.mp4", "clip_20one.mp4", "clip_5Fone.mp4"]
+        );
+    }
+
+    #[test]
+    fn one_generation_per_key_at_a_time() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let locks = KeyLocks::default();
+        let (running, most) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    locks.with("clip.mp4", || {
+                        most.fetch_max(
+                            running.fetch_add(1, Ordering::SeqCst) + 1,
+                            Ordering::SeqCst,
+                        );
+                        std::thread::sleep(std::time::Duration::from_millis(5));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                });
+            }
+        });
+        assert_eq!(most.into_inner(), 1);
+    }
+}
@@ -0,0 +1,626 @@
+//! Waveform peaks in the formats of BBC's `audiowaveform`: min/max pairs per block of
+//! samples, as JSON or the binary `.dat` layout, and generated from WAV or raw PCM.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufReader, Read};
+
+/// Samples per peak point when generating; about 10ms at 48kHz.
+pub const DEFAULT_SAMPLES_PER_PIXEL: u32 = 512;
+
+/// Layout assumed for headerless `.pcm` and `.raw` files: 48kHz stereo 16-bit.
+pub const RAW_PCM: PcmFormat = PcmFormat {
+    sample_rate: 48_000,
+    channels: 2,
+    bits_per_sample: 16,
+    float: false,
+};
+
+#[derive(Debug)]
+pub enum PeaksError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Not a file of the kind claimed; what was wrong.
+    Malformed(&'static str),
+    /// A well-formed file in an encoding this module doesn't read.
+    Unsupported(String),
+}
+
+impl fmt::Display for PeaksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Json(err) => write!(f, "{err}"),
+            Self::Malformed(what) => write!(f, "malformed {what}"),
+            Self::Unsupported(what) => write!(f, "unsupported {what}"),
+        }
+    }
+}
+
+impl std::error::Error for PeaksError {}
+
+impl From<io::Error> for PeaksError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PeaksError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Peak data: for every `samples_per_pixel` samples, the lowest and highest sample of each
+/// channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peaks {
+    pub sample_rate: u32,
+    pub samples_per_pixel: u32,
+    pub channels: u32,
+    /// 8 or 16: the range of `data`.
+    pub bits: u8,
+    /// Min/max pairs, channels interleaved within each point.
+    pub data: Vec<i16>,
+}
+
+/// The JSON layout. Version 1 files have no `channels` and are mono.
+#[derive(Serialize, Deserialize)]
+struct JsonPeaks {
+    version: u32,
+    #[serde(default = "one")]
+    channels: u32,
+    sample_rate: u32,
+    samples_per_pixel: u32,
+    bits: u8,
+    length: usize,
+    data: Vec<i16>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl Peaks {
+    /// Parse a peaks file fetched from `url`: binary for `.dat`, JSON otherwise.
+    pub fn parse(bytes: &[u8], url: &str) -> Result<Self, PeaksError> {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        if path.ends_with(".dat") {
+            Self::from_dat(bytes)
+        } else {
+            let text = std::str::from_utf8(bytes).map_err(|_| PeaksError::Malformed("JSON"))?;
+            Self::from_json(text)
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, PeaksError> {
+        let json: JsonPeaks = serde_json::from_str(text)?;
+        let peaks = Peaks {
+            sample_rate: json.sample_rate,
+            samples_per_pixel: json.samples_per_pixel,
+            channels: json.channels,
+            bits: json.bits,
+            data: json.data,
+        };
+        peaks.validate()?;
+        if peaks.len() != json.length {
+            return Err(PeaksError::Malformed("peaks length"));
+        }
+        Ok(peaks)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&JsonPeaks {
+            version: 2,
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples_per_pixel: self.samples_per_pixel,
+            bits: self.bits,
+            length: self.len(),
+            data: self.data.clone(),
+        })
+        .expect("peaks serialize")
+    }
+
+    pub fn from_dat(bytes: &[u8]) -> Result<Self, PeaksError> {
+        let field = |i: usize| -> Result<u32, PeaksError> {
+            bytes
+                .get(i * 4..i * 4 + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .ok_or(PeaksError::Malformed("peaks header"))
+        };
+        let version = field(0)?;
+        let (channels, header) = match version {
+            1 => (1, 20),
+            2 => (field(5)?, 24),
+            _ => return Err(PeaksError::Unsupported(format!("peaks version {version}"))),
+        };
+        let bits = if field(1)? & 1 == 1 { 8 } else { 16 };
+        let length = field(4)? as usize;
+        let body = &bytes[header..];
+        let data: Vec<i16> = if bits == 8 {
+            body.iter().map(|&b| b as i8 as i16).collect()
+        } else {
+            body.chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                .collect()
+        };
+        let peaks = Peaks {
+            sample_rate: field(2)?,
+            samples_per_pixel: field(3)?,
+            channels,
+            bits,
+            data,
+        };
+        peaks.validate()?;
+        if peaks.len() != length {
+            return Err(PeaksError::Malformed("peaks length"));
+        }
+        Ok(peaks)
+    }
+
+    /// The binary layout, version 2.
+    pub fn to_dat(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(24 + self.data.len() * 2);
+        for field in [
+            2,
+            (self.bits == 8) as u32,
+            self.sample_rate,
+            self.samples_per_pixel,
+            self.len() as u32,
+            self.channels,
+        ] {
+            out.extend_from_slice(&field.to_le_bytes());
+        }
+        for &value in &self.data {
+            if self.bits == 8 {
+                out.push(value as i8 as u8);
+            } else {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    fn validate(&self) -> Result<(), PeaksError> {
+        if self.sample_rate == 0 || self.samples_per_pixel == 0 || self.channels == 0 {
+            return Err(PeaksError::Malformed("peaks header"));
+        }
+        if !matches!(self.bits, 8 | 16) {
+            return Err(PeaksError::Unsupported(format!("{}-bit peaks", self.bits)));
+        }
+        if !self.data.len().is_multiple_of(2 * self.channels as usize) {
+            return Err(PeaksError::Malformed("peaks data"));
+        }
+        Ok(())
+    }
+
+    /// Number of points.
+    pub fn len(&self) -> usize {
+        self.data.len() / (2 * self.channels.max(1) as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Seconds of audio covered.
+    pub fn duration(&self) -> f64 {
+        self.len() as f64 * self.samples_per_pixel as f64 / self.sample_rate as f64
+    }
+
+    /// Lowest and highest sample of any channel from `start` to `end` seconds, scaled to
+    /// `-1.0..=1.0`. Covers at least the point `start` falls in; `None` outside the audio.
+    pub fn range(&self, start: f64, end: f64) -> Option<(f32, f32)> {
+        let points_per_second = self.sample_rate as f64 / self.samples_per_pixel as f64;
+        let first = (start * points_per_second).floor();
+        if first < 0.0 || first >= self.len() as f64 {
+            return None;
+        }
+        let first = first as usize;
+        let last = ((end * points_per_second).ceil() as usize).clamp(first + 1, self.len());
+        let stride = 2 * self.channels as usize;
+        let scale = (1i32 << (self.bits - 1)) as f32;
+        let (min, max) = self.data[first * stride..last * stride]
+            .chunks_exact(2)
+            .fold((i16::MAX, i16::MIN), |(min, max), pair| {
+                (min.min(pair[0]), max.max(pair[1]))
+            });
+        Some((min as f32 / scale, max as f32 / scale))
+    }
+}
+
+/// Accumulates samples into [`Peaks`].
+#[derive(Debug, Clone)]
+pub struct PeaksBuilder {
+    peaks: Peaks,
+    min: Vec<f32>,
+    max: Vec<f32>,
+    count: u32,
+}
+
+impl PeaksBuilder {
+    pub fn new(sample_rate: u32, channels: u32, samples_per_pixel: u32, bits: u8) -> Self {
+        PeaksBuilder {
+            peaks: Peaks {
+                sample_rate,
+                samples_per_pixel: samples_per_pixel.max(1),
+                channels,
+                bits,
+                data: Vec::new(),
+            },
+            min: vec![f32::INFINITY; channels as usize],
+            max: vec![f32::NEG_INFINITY; channels as usize],
+            count: 0,
+        }
+    }
+
+    /// Add one sample per channel, each in `-1.0..=1.0`.
+    pub fn push(&mut self, frame: &[f32]) {
+        for ((min, max), &sample) in self.min.iter_mut().zip(&mut self.max).zip(frame) {
+            *min = min.min(sample);
+            *max = max.max(sample);
+        }
+        self.count += 1;
+        if self.count == self.peaks.samples_per_pixel {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let scale = ((1i32 << (self.peaks.bits - 1)) - 1) as f32;
+        let quantize = |v: f32| (v * scale).round().clamp(-scale - 1.0, scale) as i16;
+        for (min, max) in self.min.iter_mut().zip(&mut self.max) {
+            self.peaks.data.push(quantize(*min));
+            self.peaks.data.push(quantize(*max));
+            *min = f32::INFINITY;
+            *max = f32::NEG_INFINITY;
+        }
+        self.count = 0;
+    }
+
+    /// The peaks, including a last, partial point.
+    pub fn finish(mut self) -> Peaks {
+        if self.count > 0 {
+            self.flush();
+        }
+        self.peaks
+    }
+}
+
+/// Sample layout of uncompressed audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// 8 (unsigned), 16, 24 or 32 for integers; 32 or 64 for floats. Little-endian.
+    pub bits_per_sample: u16,
+    pub float: bool,
+}
+
+impl PcmFormat {
+    fn decode(&self, bytes: &[u8]) -> f32 {
+        match (self.float, self.bits_per_sample) {
+            (false, 8) => (bytes[0] as f32 - 128.0) / 128.0,
+            (false, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32_768.0,
+            (false, 24) => {
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8_388_608.0
+            }
+            (false, 32) => {
+                i32::from_le_bytes(bytes[..4].try_into().unwrap()) as f32 / 2_147_483_648.0
+            }
+            (true, 32) => f32::from_le_bytes(bytes[..4].try_into().unwrap()),
+            (true, 64) => f64::from_le_bytes(bytes[..8].try_into().unwrap()) as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn check(&self) -> Result<(), PeaksError> {
+        let supported = match self.float {
+            false => matches!(self.bits_per_sample, 8 | 16 | 24 | 32),
+            true => matches!(self.bits_per_sample, 32 | 64),
+        };
+        if !supported || self.channels == 0 || self.sample_rate == 0 {
+            return Err(PeaksError::Unsupported(format!(
+                "{}-bit {} audio",
+                self.bits_per_sample,
+                if self.float { "float" } else { "PCM" }
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Peaks of interleaved PCM samples in `format`, read to the end of `reader`.
+pub fn from_pcm<R: Read>(
+    reader: R,
+    format: PcmFormat,
+    samples_per_pixel: u32,
+    bits: u8,
+) -> Result<Peaks, PeaksError> {
+    format.check()?;
+    let sample_bytes = format.bits_per_sample as usize / 8;
+    let channels = format.channels as usize;
+    let mut builder =
+        PeaksBuilder::new(format.sample_rate, channels as u32, samples_per_pixel, bits);
+    let mut reader = BufReader::new(reader);
+    let mut bytes = vec![0; sample_bytes * channels];
+    let mut frame = vec![0.0; channels];
+    loop {
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => {}
+            // A trailing partial frame is dropped.
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err.into()),
+        }
+        for (sample, bytes) in frame.iter_mut().zip(bytes.chunks_exact(sample_bytes)) {
+            *sample = format.decode(bytes);
+        }
+        builder.push(&frame);
+    }
+    Ok(builder.finish())
+}
+
+/// Read a WAV header up to the start of the sample data. Returns the sample format and the
+/// length of the data in bytes, if the header gives one.
+pub fn read_wav_header<R: Read>(reader: &mut R) -> Result<(PcmFormat, Option<u64>), PeaksError> {
+    let mut riff = [0; 12];
+    reader.read_exact(&mut riff)?;
+    if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+        return Err(PeaksError::Malformed("WAV header"));
+    }
+    let mut format = None;
+    loop {
+        let mut chunk = [0; 8];
+        reader
+            .read_exact(&mut chunk)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => PeaksError::Malformed("WAV: no data chunk"),
+                _ => err.into(),
+            })?;
+        let size = u32::from_le_bytes(chunk[4..].try_into().unwrap());
+        match &chunk[..4] {
+            b"fmt " => {
+                if size < 16 {
+                    return Err(PeaksError::Malformed("WAV fmt chunk"));
+                }
+                let mut fmt = vec![0; size as usize + (size & 1) as usize];
+                reader.read_exact(&mut fmt)?;
+                let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]);
+                let mut tag = u16_at(0);
+                // WAVE_FORMAT_EXTENSIBLE keeps the real tag at the start of the sub-format GUID.
+                if tag == 0xfffe && size >= 26 {
+                    tag = u16_at(24);
+                }
+                let pcm = PcmFormat {
+                    channels: u16_at(2),
+                    sample_rate: u32::from_le_bytes(fmt[4..8].try_into().unwrap()),
+                    bits_per_sample: u16_at(14),
+                    float: tag == 3,
+                };
+                if !matches!(tag, 1 | 3) {
+                    return Err(PeaksError::Unsupported(format!("WAV format {tag:#x}")));
+                }
+                pcm.check()?;
+                format = Some(pcm);
+            }
+            b"data" => {
+                let format = format.ok_or(PeaksError::Malformed("WAV: data before fmt"))?;
+                // Streaming writers leave the size zero or maxed out.
+                let len = (size != 0 && size != u32::MAX).then_some(size as u64);
+                return Ok((format, len));
+            }
+            _ => {
+                let skip = size as u64 + (size & 1) as u64;
+                io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+            }
+        }
+    }
+}
+
+/// Peaks of a WAV file.
+pub fn from_wav<R: Read>(
+    mut reader: R,
+    samples_per_pixel: u32,
+    bits: u8,
+) -> Result<Peaks, PeaksError> {
+    let (format, len) = read_wav_header(&mut reader)?;
+    match len {
+        Some(len) => from_pcm(reader.take(len), format, samples_per_pixel, bits),
+        None => from_pcm(reader, format, samples_per_pixel, bits),
+    }
+}
+
+/// Generates and caches peaks for the audio files under a media root.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone)]
+pub struct PeaksCache {
+    /// Generated `.dat` files are kept under `peaks/` here.
+    pub cache_root: std::path::PathBuf,
+    pub media_root: std::path::PathBuf,
+    pub samples_per_pixel: u32,
+}
+
+#[cfg(feature = "ssr")]
+impl PeaksCache {
+    /// Peaks of `media` (relative to the media root): a `.wav` file, or raw [`RAW_PCM`] for
+    /// `.pcm` and `.raw`. Cached until the media changes. `Ok(None)` if there's no such
+    /// file.
+    pub fn peaks(&self, media: &str) -> Result<Option<Peaks>, PeaksError> {
+        use crate::paths::{cache_key, safe_relative};
+        use std::fs::{self, File};
+
+        let Some(relative) = safe_relative(media) else {
+            return Ok(None);
+        };
+        let media_path = self.media_root.join(&relative);
+        let Ok(metadata) = fs::metadata(&media_path) else {
+            return Ok(None);
+        };
+        let extension = media_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        let cached_path = self.cache_root.join("peaks").join(format!(
+            "{}.{}.dat",
+            cache_key(&relative),
+            self.samples_per_pixel
+        ));
+        if let Ok(cached) = fs::metadata(&cached_path) {
+            if cached.modified()? >= metadata.modified()? {
+                return Peaks::from_dat(&fs::read(&cached_path)?).map(Some);
+            }
+        }
+
+        let file = File::open(&media_path)?;
+        let peaks = match extension.as_deref() {
+            Some("wav" | "wave") => from_wav(file, self.samples_per_pixel, 16)?,
+            Some("pcm" | "raw") => from_pcm(file, RAW_PCM, self.samples_per_pixel, 16)?,
+            _ => return Err(PeaksError::Unsupported(format!("audio file {media}"))),
+        };
+        fs::create_dir_all(self.cache_root.join("peaks"))?;
+        fs::write(&cached_path, peaks.to_dat())?;
+        Ok(Some(peaks))
+    }
+}
+
+/// Axum service answering `GET /{*media}.json` and `GET /{*media}.dat` with the peaks of
+/// `media` in that format; nest it under a prefix such as `/api/peaks`.
+#[cfg(feature = "ssr")]
+pub fn service(cache: PeaksCache) -> axum::Router {
+    use crate::paths::{cache_key, safe_relative, KeyLocks};
+    use axum::extract::{Path as UrlPath, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+
+    async fn peaks(
+        State((cache, locks)): State<(PeaksCache, KeyLocks)>,
+        UrlPath(path): UrlPath<String>,
+    ) -> Response {
+        let (media, json) = if let Some(media) = path.strip_suffix(".json") {
+            (media.to_string(), true)
+        } else if let Some(media) = path.strip_suffix(".dat") {
+            (media.to_string(), false)
+        } else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let key = safe_relative(&media).map_or_else(|| media.clone(), |r| cache_key(&r));
+        // One generation per file; waiters then read the cached peaks.
+        let result =
+            tokio::task::spawn_blocking(move || locks.with(&key, || cache.peaks(&media))).await;
+        match result {
+            Ok(Ok(Some(peaks))) if json => (
+                [(header::CONTENT_TYPE, "application/json")],
+                peaks.to_json(),
+            )
+                .into_response(),
+            Ok(Ok(Some(peaks))) => (
+                [(header::CONTENT_TYPE, "application/octet-stream")],
+                peaks.to_dat(),
+            )
+                .into_response(),
+            Ok(Ok(None)) => StatusCode::NOT_FOUND.into_response(),
+            Ok(Err(err @ PeaksError::Unsupported(_))) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, err.to_string()).into_response()
+            }
+            Ok(Err(err)) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+
+    axum::Router::new()
+        .route("/{*media}", get(peaks))
+        .with_state((cache, KeyLocks::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WAV file of 16-bit stereo: a ramp on the left, silence on the right.
+    fn wav(frames: usize) -> Vec<u8> {
+        let data_len = frames as u32 * 4;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + 12 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        for field in [1u16, 2] {
+            wav.extend_from_slice(&field.to_le_bytes());
+        }
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&(8000u32 * 4).to_le_bytes());
+        for field in [4u16, 16] {
+            wav.extend_from_slice(&field.to_le_bytes());
+        }
+        // An odd-sized chunk before the data, padded to an even length.
+        wav.extend_from_slice(b"LIST");
+        wav.extend_from_slice(&3u32.to_le_bytes());
+        wav.extend_from_slice(b"abc\0");
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for i in 0..frames {
+            let left = (i as i32 * 32_768 / frames as i32 * 2 - 32_768) as i16;
+            wav.extend_from_slice(&left.to_le_bytes());
+            wav.extend_from_slice(&0i16.to_le_bytes());
+        }
+        // Trailing metadata isn't audio.
+        wav.extend_from_slice(b"id3 \x04\0\0\0junk");
+        wav
+    }
+
+    #[test]
+    fn peaks_from_wav() {
+        let peaks = from_wav(&wav(1000)[..], 400, 8).unwrap();
+        assert_eq!(
+            (peaks.sample_rate, peaks.channels, peaks.bits),
+            (8000, 2, 8)
+        );
+        assert_eq!(peaks.len(), 3);
+        assert_eq!(peaks.duration(), 0.15);
+        // The left ramp climbs from -1 through 0; the right is silent; the last point is
+        // the partial one.
+        assert_eq!(&peaks.data[..4], [-127, -26, 0, 0]);
+        assert_eq!(&peaks.data[8..], [76, 127, 0, 0]);
+
+        let (min, max) = peaks.range(0.0, 0.15).unwrap();
+        assert!(min <= -0.99 && max >= 0.99);
+        assert_eq!(peaks.range(0.06, 0.06), Some((-0.1953125, 0.59375)));
+        assert_eq!(peaks.range(0.2, 0.3), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        let peaks = from_wav(&wav(1000)[..], 256, 16).unwrap();
+        assert_eq!(Peaks::from_dat(&peaks.to_dat()).unwrap(), peaks);
+        assert_eq!(Peaks::from_json(&peaks.to_json()).unwrap(), peaks);
+        assert_eq!(
+            Peaks::parse(&peaks.to_dat(), "/api/peaks/a.wav.dat?v=1").unwrap(),
+            peaks
+        );
+
+        // audiowaveform's version 1 JSON is mono.
+        let v1 = r#"{"version":1,"sample_rate":48000,"samples_per_pixel":512,"bits":8,"length":2,"data":[-10,10,-64,127]}"#;
+        let peaks = Peaks::from_json(v1).unwrap();
+        assert_eq!(peaks.channels, 1);
+        assert_eq!(peaks.range(0.0, 0.0), Some((-10.0 / 128.0, 10.0 / 128.0)));
+        assert!(matches!(
+            Peaks::from_json(&v1.replace("\"length\":2", "\"length\":3")),
+            Err(PeaksError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_compressed_wav() {
+        let mut wav = wav(10);
+        wav[20] = 0x55; // MPEG layer 3
+        assert!(matches!(
+            from_wav(&wav[..], 256, 8),
+            Err(PeaksError::Unsupported(_))
+        ));
+        assert!(matches!(
+            from_wav(&b"RIFX"[..], 256, 8),
+            Err(PeaksError::Io(_))
+        ));
+    }
+}
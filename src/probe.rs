@@ -0,0 +1,402 @@
+//! Metadata of MP4 and QuickTime files, read straight from the `moov` box so the server
+//! knows a file's exact frame rate without external tools.
+
+use crate::frame_rate::FrameRate;
+use crate::timecode::timecode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// The first video track.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoTrack {
+    /// Sample entry four-cc, e.g. `avc1` or `apcn`.
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    /// Media timescale over the most common sample duration.
+    pub fps: FrameRate,
+    pub frame_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioTrack {
+    pub codec: String,
+    pub channels: u32,
+    pub sample_rate: u32,
+    /// ISO 639-2 code, `None` when unset or `und`.
+    pub language: Option<String>,
+}
+
+/// Source timecode of the first frame, from a `tmcd` track.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartTimecode {
+    /// Frames since `00:00:00:00` at `rate`.
+    pub frame: i32,
+    pub rate: FrameRate,
+}
+
+impl fmt::Display for StartTimecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&timecode(self.frame, self.rate))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mp4Info {
+    /// Seconds, from the movie header.
+    pub duration: f64,
+    pub video: Option<VideoTrack>,
+    pub audio: Vec<AudioTrack>,
+    pub start_timecode: Option<StartTimecode>,
+}
+
+#[derive(Debug)]
+pub enum ProbeError {
+    Io(io::Error),
+    /// No `moov` box: not an MP4/MOV file, or one still being written.
+    NoMovie,
+    /// A box too short for what it claims to hold.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::NoMovie => write!(f, "no moov box"),
+            Self::Malformed(kind) => write!(f, "malformed {kind} box"),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl From<io::Error> for ProbeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+type FourCc = [u8; 4];
+
+fn four_cc(kind: FourCc) -> String {
+    String::from_utf8_lossy(&kind).trim_end().to_string()
+}
+
+fn be16(data: &[u8], at: usize, kind: &'static str) -> Result<u16, ProbeError> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(ProbeError::Malformed(kind))
+}
+
+fn be32(data: &[u8], at: usize, kind: &'static str) -> Result<u32, ProbeError> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(ProbeError::Malformed(kind))
+}
+
+fn be64(data: &[u8], at: usize, kind: &'static str) -> Result<u64, ProbeError> {
+    data.get(at..at + 8)
+        .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+        .ok_or(ProbeError::Malformed(kind))
+}
+
+/// The boxes directly inside `data`, as `(type, payload)`.
+fn children(data: &[u8]) -> Result<Vec<(FourCc, &[u8])>, ProbeError> {
+    let mut boxes = Vec::new();
+    let mut rest = data;
+    while rest.len() >= 8 {
+        let size = be32(rest, 0, "box")? as u64;
+        let kind: FourCc = rest[4..8].try_into().unwrap();
+        let (header, size) = match size {
+            0 => (8, rest.len() as u64),
+            1 => (16, be64(rest, 8, "box")?),
+            size => (8, size),
+        };
+        if size < header as u64 || size > rest.len() as u64 {
+            return Err(ProbeError::Malformed("box"));
+        }
+        boxes.push((kind, &rest[header..size as usize]));
+        rest = &rest[size as usize..];
+    }
+    Ok(boxes)
+}
+
+fn child<'a>(data: &'a [u8], kind: &FourCc) -> Result<Option<&'a [u8]>, ProbeError> {
+    Ok(children(data)?
+        .into_iter()
+        .find(|(k, _)| k == kind)
+        .map(|(_, body)| body))
+}
+
+/// Walk the top-level boxes and read the `moov` payload, skipping `mdat` without reading it.
+fn read_movie<R: Read + Seek>(reader: &mut R) -> Result<Vec<u8>, ProbeError> {
+    loop {
+        let mut header = [0; 8];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(ProbeError::NoMovie)
+            }
+            Err(err) => return Err(err.into()),
+        }
+        let kind = &header[4..8];
+        let (payload, to_end) = match be32(&header, 0, "box")? {
+            0 => (0, true),
+            1 => {
+                let mut large = [0; 8];
+                reader.read_exact(&mut large)?;
+                let size = u64::from_be_bytes(large);
+                (
+                    size.checked_sub(16).ok_or(ProbeError::Malformed("box"))?,
+                    false,
+                )
+            }
+            size => (
+                (size as u64)
+                    .checked_sub(8)
+                    .ok_or(ProbeError::Malformed("box"))?,
+                false,
+            ),
+        };
+        if kind == b"moov" {
+            let mut movie = Vec::new();
+            if to_end {
+                reader.read_to_end(&mut movie)?;
+            } else {
+                reader.take(payload).read_to_end(&mut movie)?;
+                if movie.len() as u64 != payload {
+                    return Err(ProbeError::Malformed("moov"));
+                }
+            }
+            return Ok(movie);
+        }
+        if to_end {
+            return Err(ProbeError::NoMovie);
+        }
+        let skip = i64::try_from(payload).map_err(|_| ProbeError::Malformed("box"))?;
+        reader.seek(SeekFrom::Current(skip))?;
+    }
+}
+
+/// `(timescale, duration)` of an `mvhd` or `mdhd` payload, plus the 16-bit field after them.
+fn header_times(body: &[u8], kind: &'static str) -> Result<(u32, u64, u16), ProbeError> {
+    if body.first() == Some(&1) {
+        Ok((
+            be32(body, 20, kind)?,
+            be64(body, 24, kind)?,
+            be16(body, 32, kind)?,
+        ))
+    } else {
+        Ok((
+            be32(body, 12, kind)?,
+            be32(body, 16, kind)? as u64,
+            be16(body, 20, kind)?,
+        ))
+    }
+}
+
+/// A packed ISO 639-2 code. Values below 0x400 are QuickTime's Macintosh language codes,
+/// which aren't mapped.
+fn language(code: u16) -> Option<String> {
+    if code < 0x400 || code == 0x7fff {
+        return None;
+    }
+    let letters: String = [10, 5, 0]
+        .iter()
+        .map(|shift| ((code >> shift & 0x1f) as u8 + 0x60) as char)
+        .collect();
+    (letters != "und" && letters.chars().all(|c| c.is_ascii_lowercase())).then_some(letters)
+}
+
+struct Track<'a> {
+    handler: FourCc,
+    timescale: u32,
+    language: u16,
+    /// First `stsd` entry: format and payload.
+    entry: Option<(FourCc, &'a [u8])>,
+    /// `stts` as `(sample count, sample duration)`.
+    sample_times: Vec<(u32, u32)>,
+    first_chunk: Option<u64>,
+}
+
+fn track(trak: &[u8]) -> Result<Option<Track<'_>>, ProbeError> {
+    let Some(mdia) = child(trak, b"mdia")? else {
+        return Ok(None);
+    };
+    let (Some(mdhd), Some(hdlr)) = (child(mdia, b"mdhd")?, child(mdia, b"hdlr")?) else {
+        return Ok(None);
+    };
+    let (timescale, _, language) = header_times(mdhd, "mdhd")?;
+    let handler: FourCc = hdlr
+        .get(8..12)
+        .ok_or(ProbeError::Malformed("hdlr"))?
+        .try_into()
+        .unwrap();
+    let stbl = match child(mdia, b"minf")? {
+        Some(minf) => child(minf, b"stbl")?,
+        None => None,
+    };
+    let mut track = Track {
+        handler,
+        timescale,
+        language,
+        entry: None,
+        sample_times: Vec::new(),
+        first_chunk: None,
+    };
+    let Some(stbl) = stbl else {
+        return Ok(Some(track));
+    };
+    for (kind, body) in children(stbl)? {
+        match &kind {
+            b"stsd" => {
+                track.entry = children(body.get(8..).ok_or(ProbeError::Malformed("stsd"))?)?
+                    .into_iter()
+                    .next();
+            }
+            b"stts" => {
+                let count = be32(body, 4, "stts")? as usize;
+                track.sample_times = (0..count)
+                    .map(|i| {
+                        Ok((
+                            be32(body, 8 + i * 8, "stts")?,
+                            be32(body, 12 + i * 8, "stts")?,
+                        ))
+                    })
+                    .collect::<Result<_, ProbeError>>()?;
+            }
+            b"stco" if be32(body, 4, "stco")? > 0 => {
+                track.first_chunk = Some(be32(body, 8, "stco")? as u64);
+            }
+            b"co64" if be32(body, 4, "co64")? > 0 => {
+                track.first_chunk = Some(be64(body, 8, "co64")?);
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(track))
+}
+
+fn video_track(track: &Track) -> Result<Option<VideoTrack>, ProbeError> {
+    let Some((format, entry)) = track.entry else {
+        return Ok(None);
+    };
+    // The most common sample duration, so one odd frame doesn't skew the rate.
+    let mut durations: Vec<(u32, u64)> = Vec::new();
+    for &(count, duration) in &track.sample_times {
+        match durations.iter_mut().find(|(d, _)| *d == duration) {
+            Some((_, total)) => *total += count as u64,
+            None => durations.push((duration, count as u64)),
+        }
+    }
+    let Some(&(duration, _)) = durations.iter().max_by_key(|(_, total)| *total) else {
+        return Ok(None);
+    };
+    if duration == 0 || track.timescale == 0 {
+        return Ok(None);
+    }
+    Ok(Some(VideoTrack {
+        codec: four_cc(format),
+        width: be16(entry, 24, "stsd")? as u32,
+        height: be16(entry, 26, "stsd")? as u32,
+        fps: FrameRate::new(track.timescale, duration),
+        frame_count: track.sample_times.iter().map(|&(n, _)| n as u64).sum(),
+    }))
+}
+
+fn audio_track(track: &Track) -> Result<Option<AudioTrack>, ProbeError> {
+    let Some((format, entry)) = track.entry else {
+        return Ok(None);
+    };
+    // QuickTime version 2 sound descriptions move the rate and channels into new fields.
+    let (channels, sample_rate) = if be16(entry, 8, "stsd")? == 2 {
+        (
+            be32(entry, 40, "stsd")?,
+            f64::from_bits(be64(entry, 32, "stsd")?).round() as u32,
+        )
+    } else {
+        (
+            be16(entry, 16, "stsd")? as u32,
+            be32(entry, 24, "stsd")? >> 16,
+        )
+    };
+    Ok(Some(AudioTrack {
+        codec: four_cc(format),
+        channels,
+        sample_rate,
+        language: language(track.language),
+    }))
+}
+
+/// Read metadata from an MP4 or MOV file. Only the `moov` box and, for a timecode track,
+/// its first sample are read.
+pub fn probe<R: Read + Seek>(mut reader: R) -> Result<Mp4Info, ProbeError> {
+    let movie = read_movie(&mut reader)?;
+    let mut info = Mp4Info::default();
+    let mut timecode_track = None;
+    for (kind, body) in children(&movie)? {
+        match &kind {
+            b"mvhd" => {
+                let (timescale, duration, _) = header_times(body, "mvhd")?;
+                if timescale > 0 {
+                    info.duration = duration as f64 / timescale as f64;
+                }
+            }
+            b"trak" => {
+                let Some(track) = track(body)? else {
+                    continue;
+                };
+                match &track.handler {
+                    b"vide" if info.video.is_none() => info.video = video_track(&track)?,
+                    b"soun" => info.audio.extend(audio_track(&track)?),
+                    b"tmcd" if timecode_track.is_none() => {
+                        if let (Some((_, entry)), Some(offset)) = (track.entry, track.first_chunk) {
+                            let rate =
+                                FrameRate::new(be32(entry, 16, "tmcd")?, be32(entry, 20, "tmcd")?);
+                            timecode_track = Some((rate, offset));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((rate, offset)) = timecode_track {
+        let mut sample = [0; 4];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut sample)?;
+        info.start_timecode = Some(StartTimecode {
+            frame: u32::from_be_bytes(sample) as i32,
+            rate,
+        });
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn languages() {
+        assert_eq!(language(0x15c7).as_deref(), Some("eng"));
+        assert_eq!(language(0x55c4), None); // und
+        assert_eq!(language(0), None); // Macintosh English
+    }
+
+    #[test]
+    fn rejects_non_movies() {
+        assert!(matches!(
+            probe(Cursor::new(b"\0\0\0\x08free")),
+            Err(ProbeError::NoMovie)
+        ));
+        assert!(matches!(
+            probe(Cursor::new(b"\0\0\0\x10moov\0\0\0\x20mvhd")),
+            Err(ProbeError::Malformed(_))
+        ));
+    }
+}
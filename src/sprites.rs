@@ -0,0 +1,629 @@
+//! Thumbnail sprite sheets and their WebVTT index, generated on the server for the scrub
+//! preview and cached on disk under the site root.
+
+use crate::paths::{cache_key, safe_relative};
+use crate::vtt::format_timestamp;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+/// An 8-bit RGB image, rows top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl RgbImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 3],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        [self.data[i], self.data[i + 1], self.data[i + 2]]
+    }
+
+    /// Copy `src` in with its top-left corner at `(x, y)`, clipping at the edges.
+    fn blit(&mut self, src: &RgbImage, x: u32, y: u32) {
+        let width = src.width.min(self.width.saturating_sub(x)) as usize * 3;
+        for row in 0..src.height.min(self.height.saturating_sub(y)) {
+            let from = row as usize * src.width as usize * 3;
+            let to = ((y + row) as usize * self.width as usize + x as usize) * 3;
+            self.data[to..to + width].copy_from_slice(&src.data[from..from + width]);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SpriteError {
+    /// The media file doesn't exist or the path escapes the media root.
+    NotFound,
+    Io(io::Error),
+    Decode(String),
+}
+
+impl fmt::Display for SpriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "media not found"),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Decode(msg) => write!(f, "decode failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SpriteError {}
+
+impl From<io::Error> for SpriteError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Extracts frames from media files.
+pub trait FrameDecoder: Send + Sync {
+    /// Duration of `path` in seconds.
+    fn duration(&self, path: &Path) -> Result<f64, SpriteError>;
+
+    /// The frame shown at `time` seconds, scaled to exactly `width`×`height`.
+    fn frame(
+        &self,
+        path: &Path,
+        time: f64,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbImage, SpriteError>;
+
+    /// File extension of the sheets [`encode_sheet`](Self::encode_sheet) writes.
+    fn sheet_extension(&self) -> &'static str {
+        "png"
+    }
+
+    /// Encode a finished sprite sheet, or a poster. Uncompressed PNG unless the decoder can
+    /// do better.
+    fn encode_sheet(&self, sheet: &RgbImage) -> Result<Vec<u8>, SpriteError> {
+        Ok(encode_png(sheet))
+    }
+}
+
+/// Decodes with the `ffmpeg` and `ffprobe` executables on `PATH`.
+#[derive(Debug, Clone)]
+pub struct FfmpegDecoder {
+    pub ffmpeg: PathBuf,
+    pub ffprobe: PathBuf,
+}
+
+impl Default for FfmpegDecoder {
+    fn default() -> Self {
+        Self {
+            ffmpeg: "ffmpeg".into(),
+            ffprobe: "ffprobe".into(),
+        }
+    }
+}
+
+pub(crate) fn run(command: &mut Command) -> Result<Vec<u8>, SpriteError> {
+    let output = command
+        .output()
+        .map_err(|err| SpriteError::Decode(format!("{command:?}: {err}")))?;
+    check_output(output)
+}
+
+/// Run `command` with `input` piped to its stdin.
+fn run_with_input(command: &mut Command, input: &[u8]) -> Result<Vec<u8>, SpriteError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| SpriteError::Decode(format!("{command:?}: {err}")))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Fed from another thread so a full stdout pipe can't stall the write.
+    let output = std::thread::scope(|scope| {
+        scope.spawn(move || stdin.write_all(input));
+        child.wait_with_output()
+    })?;
+    check_output(output)
+}
+
+fn check_output(output: std::process::Output) -> Result<Vec<u8>, SpriteError> {
+    if !output.status.success() {
+        return Err(SpriteError::Decode(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+impl FrameDecoder for FfmpegDecoder {
+    fn duration(&self, path: &Path) -> Result<f64, SpriteError> {
+        let out = run(Command::new(&self.ffprobe)
+            .args([
+                "-v",
+                "error",
+                "-show_entries",
+                "format=duration",
+                "-of",
+                "csv=p=0",
+            ])
+            .arg(path))?;
+        String::from_utf8_lossy(&out)
+            .trim()
+            .parse()
+            .map_err(|_| SpriteError::Decode("ffprobe printed no duration".to_string()))
+    }
+
+    fn frame(
+        &self,
+        path: &Path,
+        time: f64,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbImage, SpriteError> {
+        let data = run(Command::new(&self.ffmpeg)
+            .args(["-v", "error", "-ss", &format!("{time:.3}"), "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-vf"])
+            .arg(format!(
+                "scale={width}:{height}:force_original_aspect_ratio=decrease,pad={width}:{height}:(ow-iw)/2:(oh-ih)/2"
+            ))
+            .args(["-f", "rawvideo", "-pix_fmt", "rgb24", "-"]))?;
+        if data.len() != width as usize * height as usize * 3 {
+            return Err(SpriteError::Decode(format!(
+                "expected a {width}x{height} frame, got {} bytes",
+                data.len()
+            )));
+        }
+        Ok(RgbImage {
+            width,
+            height,
+            data,
+        })
+    }
+
+    fn sheet_extension(&self) -> &'static str {
+        "jpg"
+    }
+
+    fn encode_sheet(&self, sheet: &RgbImage) -> Result<Vec<u8>, SpriteError> {
+        run_with_input(
+            Command::new(&self.ffmpeg)
+                .args(["-v", "error", "-f", "rawvideo", "-pix_fmt", "rgb24", "-s"])
+                .arg(format!("{}x{}", sheet.width, sheet.height))
+                .args(["-i", "-", "-frames:v", "1", "-q:v", "4", "-f", "mjpeg", "-"]),
+            &sheet.data,
+        )
+    }
+}
+
+/// Sprite sheet layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteOptions {
+    /// Seconds between thumbnails.
+    pub interval: f64,
+    /// Size of one thumbnail in pixels.
+    pub width: u32,
+    pub height: u32,
+    /// Thumbnails per sheet row and column.
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl Default for SpriteOptions {
+    fn default() -> Self {
+        Self {
+            interval: 2.0,
+            width: 160,
+            height: 90,
+            columns: 10,
+            rows: 10,
+        }
+    }
+}
+
+/// Sheets and their WebVTT index, before anything is written out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprites {
+    pub sheets: Vec<RgbImage>,
+    pub vtt: String,
+}
+
+/// Decode a thumbnail every `options.interval` seconds of `media` and pack them into sheets.
+///
+/// VTT cues point at `{url_prefix}sprite_{n}.{ext}#xywh=…`, with the decoder's
+/// [`sheet_extension`](FrameDecoder::sheet_extension).
+pub fn generate(
+    decoder: &dyn FrameDecoder,
+    media: &Path,
+    url_prefix: &str,
+    options: SpriteOptions,
+) -> Result<Sprites, SpriteError> {
+    let SpriteOptions {
+        interval,
+        width,
+        height,
+        columns,
+        rows,
+    } = options;
+    let duration = decoder.duration(media)?;
+    let count = (duration / interval).ceil().max(1.0) as u32;
+    let per_sheet = columns * rows;
+    let ext = decoder.sheet_extension();
+
+    let mut sheets: Vec<RgbImage> = Vec::new();
+    let mut vtt = String::from("WEBVTT\n");
+    for i in 0..count {
+        let (sheet, slot) = (i / per_sheet, i % per_sheet);
+        if slot == 0 {
+            // The last sheet only has as many rows as it needs.
+            let used_rows = (count - i).min(per_sheet).div_ceil(columns);
+            sheets.push(RgbImage::new(width * columns, height * used_rows));
+        }
+        let start = i as f64 * interval;
+        let end = (start + interval).min(duration);
+        let (x, y) = (slot % columns * width, slot / columns * height);
+        let frame = decoder.frame(media, start, width, height)?;
+        sheets[sheet as usize].blit(&frame, x, y);
+        vtt.push_str(&format!(
+            "\n{} --> {}\n{url_prefix}sprite_{sheet}.{ext}#xywh={x},{y},{width},{height}\n",
+            format_timestamp(start),
+            format_timestamp(end),
+        ));
+    }
+    Ok(Sprites { sheets, vtt })
+}
+
+/// Where generated thumbnails go: `{site_root}/thumbnails/{key}/`, served as `/thumbnails/{key}/`.
+#[derive(Clone)]
+pub struct SpriteCache {
+    pub site_root: PathBuf,
+    /// Directory media paths are resolved against.
+    pub media_root: PathBuf,
+    pub options: SpriteOptions,
+}
+
+impl SpriteCache {
+    /// The VTT index for `media` (relative to the media root), generating and caching the
+    /// sheets first unless a cached index is newer than the media file.
+    pub fn vtt(&self, decoder: &dyn FrameDecoder, media: &str) -> Result<String, SpriteError> {
+        let (media_path, media_modified, key) = self.locate(media)?;
+        let dir = self.site_root.join("thumbnails").join(&key);
+        let vtt_path = dir.join("thumbs.vtt");
+        if is_fresh(&vtt_path, media_modified)? {
+            return Ok(fs::read_to_string(&vtt_path)?);
+        }
+
+        let sprites = generate(
+            decoder,
+            &media_path,
+            &format!("/thumbnails/{key}/"),
+            self.options,
+        )?;
+        fs::create_dir_all(&dir)?;
+        let ext = decoder.sheet_extension();
+        for (n, sheet) in sprites.sheets.iter().enumerate() {
+            fs::write(
+                dir.join(format!("sprite_{n}.{ext}")),
+                decoder.encode_sheet(sheet)?,
+            )?;
+        }
+        // Written last, so a half-finished run never looks cached.
+        fs::write(&vtt_path, &sprites.vtt)?;
+        Ok(sprites.vtt)
+    }
+
+    /// One thumbnail of `media`, a third of the way in where it's more telling than the
+    /// often-black first frame: a poster for the library that costs a single decode rather
+    /// than the whole track. Cached as `poster.{ext}` beside the sheets.
+    pub fn poster(&self, decoder: &dyn FrameDecoder, media: &str) -> Result<Vec<u8>, SpriteError> {
+        let (media_path, media_modified, key) = self.locate(media)?;
+        let dir = self.site_root.join("thumbnails").join(&key);
+        let poster_path = dir.join(format!("poster.{}", decoder.sheet_extension()));
+        if is_fresh(&poster_path, media_modified)? {
+            return Ok(fs::read(&poster_path)?);
+        }
+
+        let duration = decoder.duration(&media_path)?;
+        let SpriteOptions { width, height, .. } = self.options;
+        let frame = decoder.frame(&media_path, duration / 3.0, width, height)?;
+        let image = decoder.encode_sheet(&frame)?;
+        fs::create_dir_all(&dir)?;
+        // Moved into place once whole, so a half-written poster never looks cached.
+        let partial = dir.join("poster.partial");
+        fs::write(&partial, &image)?;
+        fs::rename(&partial, &poster_path)?;
+        Ok(image)
+    }
+
+    /// `media`'s path on disk, when it was last modified and its cache key.
+    fn locate(&self, media: &str) -> Result<(PathBuf, SystemTime, String), SpriteError> {
+        let relative = safe_relative(media).ok_or(SpriteError::NotFound)?;
+        let media_path = self.media_root.join(&relative);
+        let modified = fs::metadata(&media_path)
+            .map_err(|_| SpriteError::NotFound)?
+            .modified()?;
+        Ok((media_path, modified, cache_key(&relative)))
+    }
+}
+
+/// Whether `path` exists and was written no earlier than `since`.
+fn is_fresh(path: &Path, since: SystemTime) -> io::Result<bool> {
+    match fs::metadata(path) {
+        Ok(cached) => Ok(cached.modified()? >= since),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Encode as an uncompressed PNG: stored deflate blocks, so about 3 bytes a pixel, or
+/// 4.3 MB for a default sheet. The dependency-free fallback for decoders that can't
+/// compress; [`FfmpegDecoder`] writes JPEG instead.
+pub fn encode_png(image: &RgbImage) -> Vec<u8> {
+    let stride = image.width as usize * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * image.height as usize);
+    for row in image.data.chunks(stride.max(1)).take(image.height as usize) {
+        raw.push(0); // filter: none
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&image.width.to_be_bytes());
+    ihdr.extend_from_slice(&image.height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &ihdr), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Axum service answering `GET /{*media}` with the media's thumbnail VTT; nest it under a
+/// prefix such as `/api/thumbnails`. Sprite sheets themselves are served from the site root.
+#[cfg(feature = "ssr")]
+pub fn service<D: FrameDecoder + 'static>(cache: SpriteCache, decoder: D) -> axum::Router {
+    use crate::paths::KeyLocks;
+    use axum::extract::{Path as UrlPath, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use std::sync::Arc;
+
+    async fn thumbnails(
+        State((cache, decoder, locks)): State<(SpriteCache, Arc<dyn FrameDecoder>, KeyLocks)>,
+        UrlPath(media): UrlPath<String>,
+    ) -> Response {
+        let key = safe_relative(&media).map_or_else(|| media.clone(), |r| cache_key(&r));
+        let result = tokio::task::spawn_blocking(move || {
+            // Waiters find the index cached once the first request has generated it.
+            locks.with(&key, || cache.vtt(decoder.as_ref(), &media))
+        })
+        .await;
+        match result {
+            Ok(Ok(vtt)) => {
+                ([(header::CONTENT_TYPE, "text/vtt; charset=utf-8")], vtt).into_response()
+            }
+            Ok(Err(SpriteError::NotFound)) => StatusCode::NOT_FOUND.into_response(),
+            Ok(Err(err)) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+
+    let decoder: Arc<dyn FrameDecoder> = Arc::new(decoder);
+    axum::Router::new()
+        .route("/{*media}", get(thumbnails))
+        .with_state((cache, decoder, KeyLocks::default()))
+}
+
+/// Axum service answering `GET /{*media}` with the media's [poster](SpriteCache::poster);
+/// nest it under a prefix such as `/api/posters`.
+#[cfg(feature = "ssr")]
+pub fn poster_service<D: FrameDecoder + 'static>(cache: SpriteCache, decoder: D) -> axum::Router {
+    use crate::paths::KeyLocks;
+    use axum::extract::{Path as UrlPath, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use std::sync::Arc;
+
+    async fn poster(
+        State((cache, decoder, locks)): State<(SpriteCache, Arc<dyn FrameDecoder>, KeyLocks)>,
+        UrlPath(media): UrlPath<String>,
+    ) -> Response {
+        let key = safe_relative(&media).map_or_else(|| media.clone(), |r| cache_key(&r));
+        let content_type = match decoder.sheet_extension() {
+            "jpg" => "image/jpeg",
+            _ => "image/png",
+        };
+        let result = tokio::task::spawn_blocking(move || {
+            locks.with(&key, || cache.poster(decoder.as_ref(), &media))
+        })
+        .await;
+        match result {
+            Ok(Ok(image)) => ([(header::CONTENT_TYPE, content_type)], image).into_response(),
+            Ok(Err(SpriteError::NotFound)) => StatusCode::NOT_FOUND.into_response(),
+            Ok(Err(err)) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+
+    let decoder: Arc<dyn FrameDecoder> = Arc::new(decoder);
+    axum::Router::new()
+        .route("/{*media}", get(poster))
+        .with_state((cache, decoder, KeyLocks::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic media: every frame is a flat color encoding its timestamp.
+    struct StubDecoder {
+        duration: f64,
+    }
+
+    fn shade(time: f64) -> [u8; 3] {
+        [time as u8, 0x80, 0xff]
+    }
+
+    impl FrameDecoder for StubDecoder {
+        fn duration(&self, _: &Path) -> Result<f64, SpriteError> {
+            Ok(self.duration)
+        }
+
+        fn frame(
+            &self,
+            _: &Path,
+            time: f64,
+            width: u32,
+            height: u32,
+        ) -> Result<RgbImage, SpriteError> {
+            let mut image = RgbImage::new(width, height);
+            for pixel in image.data.chunks_mut(3) {
+                pixel.copy_from_slice(&shade(time));
+            }
+            Ok(image)
+        }
+    }
+
+    const OPTIONS: SpriteOptions = SpriteOptions {
+        interval: 1.0,
+        width: 4,
+        height: 2,
+        columns: 3,
+        rows: 2,
+    };
+
+    #[test]
+    fn packs_thumbnails_into_sheets() {
+        let decoder = StubDecoder { duration: 7.5 };
+        let sprites = generate(&decoder, Path::new("clip.mp4"), "/t/", OPTIONS).unwrap();
+        // Eight thumbnails: one full 3x2 sheet and one with a single row.
+        assert_eq!(sprites.sheets.len(), 2);
+        assert_eq!((sprites.sheets[0].width, sprites.sheets[0].height), (12, 4));
+        assert_eq!((sprites.sheets[1].width, sprites.sheets[1].height), (12, 2));
+        // Thumbnail 4 is sheet 0, row 1, column 1.
+        assert_eq!(sprites.sheets[0].pixel(5, 3), shade(4.0));
+        assert_eq!(sprites.sheets[1].pixel(4, 0), shade(7.0));
+
+        let cues = crate::vtt::parse(&sprites.vtt).unwrap();
+        assert_eq!(cues.len(), 8);
+        assert_eq!(cues[4].text, "/t/sprite_0.png#xywh=4,2,4,2");
+        assert_eq!((cues[7].start, cues[7].end), (7.0, 7.5));
+        assert_eq!(cues[7].text, "/t/sprite_1.png#xywh=4,0,4,2");
+    }
+
+    #[test]
+    fn png_structure() {
+        let png = encode_png(&RgbImage::new(3, 2));
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 3);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 2);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn caches_on_disk() {
+        let root = std::env::temp_dir().join(format!("sprites-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("clip one.mp4"), b"").unwrap();
+        let cache = SpriteCache {
+            site_root: root.clone(),
+            media_root: root.clone(),
+            options: OPTIONS,
+        };
+
+        let vtt = cache
+            .vtt(&StubDecoder { duration: 3.0 }, "clip one.mp4")
+            .unwrap();
+        assert!(vtt.contains("/thumbnails/clip_20one.mp4/sprite_0.png#xywh=0,0,4,2"));
+        assert!(root.join("thumbnails/clip_20one.mp4/sprite_0.png").exists());
+        // A cached index is served without decoding again.
+        let again = cache
+            .vtt(&StubDecoder { duration: 99.0 }, "clip one.mp4")
+            .unwrap();
+        assert_eq!(again, vtt);
+        assert!(matches!(
+            cache.vtt(&StubDecoder { duration: 1.0 }, "missing.mp4"),
+            Err(SpriteError::NotFound)
+        ));
+
+        // The poster is the frame a third of the way in, cached the same way.
+        let poster = cache
+            .poster(&StubDecoder { duration: 6.0 }, "clip one.mp4")
+            .unwrap();
+        assert_eq!(
+            poster,
+            encode_png(
+                &StubDecoder { duration: 6.0 }
+                    .frame(Path::new(""), 2.0, 4, 2)
+                    .unwrap()
+            )
+        );
+        assert!(root.join("thumbnails/clip_20one.mp4/poster.png").exists());
+        let again = cache
+            .poster(&StubDecoder { duration: 99.0 }, "clip one.mp4")
+            .unwrap();
+        assert_eq!(again, poster);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use crate::vtt::{self, VttError};
+
+/// One entry of a WebVTT thumbnail track: the image, or region of a sprite sheet, to show
+/// for `start..end` seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    pub start: f64,
+    pub end: f64,
+    /// Image URL, resolved against the track's URL.
+    pub url: String,
+    /// `(x, y, width, height)` in sprite-sheet pixels, from a `#xywh=` fragment.
+    pub region: Option<(u32, u32, u32, u32)>,
+}
+
+/// Parse a thumbnail track whose cue payloads are `image.jpg` or `sprite.jpg#xywh=x,y,w,h`.
+///
+/// Relative image URLs are resolved against `track_url`.
+pub fn parse_track(text: &str, track_url: &str) -> Result<Vec<Thumbnail>, VttError> {
+    let mut thumbnails: Vec<_> = vtt::parse(text)?
+        .into_iter()
+        .filter_map(|cue| {
+            let payload = cue.text.lines().next()?.trim();
+            let (url, region) = match payload.split_once("#xywh=") {
+                Some((url, xywh)) => (url, parse_xywh(xywh)),
+                None => (payload, None),
+            };
+            (!url.is_empty()).then(|| Thumbnail {
+                start: cue.start,
+                end: cue.end,
+                url: resolve(track_url, url),
+                region,
+            })
+        })
+        .collect();
+    thumbnails.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(thumbnails)
+}
+
+fn parse_xywh(xywh: &str) -> Option<(u32, u32, u32, u32)> {
+    let xywh = xywh.strip_prefix("pixel:").unwrap_or(xywh);
+    let mut fields = xywh.split(',').map(|f| f.trim().parse::<u32>().ok());
+    let region = (
+        fields.next()??,
+        fields.next()??,
+        fields.next()??,
+        fields.next()??,
+    );
+    fields.next().is_none().then_some(region)
+}
+
+/// `url` relative to the directory of `base`, unless it is already absolute.
+fn resolve(base: &str, url: &str) -> String {
+    if url.starts_with('/') || url.contains("://") || url.starts_with("data:") {
+        return url.to_string();
+    }
+    match base.rfind('/') {
+        Some(i) => format!("{}{url}", &base[..=i]),
+        None => url.to_string(),
+    }
+}
+
+/// The thumbnail covering media time `time`.
+pub fn find(thumbnails: &[Thumbnail], time: f64) -> Option<&Thumbnail> {
+    let i = thumbnails.partition_point(|t| t.start <= time);
+    thumbnails[..i].last().filter(|t| time < t.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACK: &str = "WEBVTT\n\n00:00.000 --> 00:05.000\nsprite.jpg#xywh=0,0,160,90\n\n00:05.000 --> 00:10.000\nsprite.jpg#xywh=160,0,160,90\n\n00:10.000 --> 00:15.000\n/abs/frame3.jpg\n";
+
+    #[test]
+    fn parses_sprite_regions() {
+        let thumbnails = parse_track(TRACK, "/media/clip/thumbs.vtt").unwrap();
+        assert_eq!(thumbnails.len(), 3);
+        assert_eq!(thumbnails[1].url, "/media/clip/sprite.jpg");
+        assert_eq!(thumbnails[1].region, Some((160, 0, 160, 90)));
+        assert_eq!(thumbnails[2].url, "/abs/frame3.jpg");
+        assert_eq!(thumbnails[2].region, None);
+    }
+
+    #[test]
+    fn finds_by_time() {
+        let thumbnails = parse_track(TRACK, "thumbs.vtt").unwrap();
+        assert_eq!(
+            find(&thumbnails, 0.0).unwrap().region,
+            Some((0, 0, 160, 90))
+        );
+        assert_eq!(
+            find(&thumbnails, 5.0).unwrap().region,
+            Some((160, 0, 160, 90))
+        );
+        assert!(find(&thumbnails, 15.0).is_none());
+        assert!(find(&[], 1.0).is_none());
+    }
+}
@@ -0,0 +1,450 @@
+use crate::frame_rate::FrameRate;
+use std::fmt;
+
+/// SMPTE timecode, either non-drop (`HH:MM:SS:FF`) or drop-frame (`HH:MM:SS;FF`).
+///
+/// `fps` is the nominal (integer) timebase the fields are counted in, e.g. 30 for 29.97.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub fps: u32,
+    pub drop_frame: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimecodeError {
+    Format,
+    OutOfRange,
+    DroppedFrame,
+    DropFrameRate(u32),
+}
+
+impl fmt::Display for TimecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format => write!(f, "expected HH:MM:SS:FF"),
+            Self::OutOfRange => write!(f, "timecode field out of range"),
+            Self::DroppedFrame => write!(f, "frame number is dropped in drop-frame timecode"),
+            Self::DropFrameRate(fps) => write!(f, "drop-frame is not defined for {fps} fps"),
+        }
+    }
+}
+
+impl std::error::Error for TimecodeError {}
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Split `HH:MM:SS:FF`-style text on any timecode separator into numeric fields.
+fn parse_fields(s: &str) -> Result<Vec<u32>, TimecodeError> {
+    s.split([':', ';', '.'])
+        .map(|f| {
+            if f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(TimecodeError::Format);
+            }
+            f.parse::<u32>().map_err(|_| TimecodeError::OutOfRange)
+        })
+        .collect()
+}
+
+/// Frame numbers skipped at the start of every minute except each tenth.
+fn dropped_per_minute(fps: u32) -> Option<u32> {
+    match fps {
+        30 => Some(2),
+        60 => Some(4),
+        _ => None,
+    }
+}
+
+impl Timecode {
+    /// Timecode for a zero-based frame count. Counts past 24 hours wrap around to 00:00:00:00,
+    /// and negative frames count back from midnight.
+    pub fn from_frame(frame: i32, fps: u32, drop_frame: bool) -> Result<Self, TimecodeError> {
+        let fps = fps.max(1);
+        let drop = if drop_frame {
+            dropped_per_minute(fps).ok_or(TimecodeError::DropFrameRate(fps))?
+        } else {
+            0
+        };
+
+        let per_minute = fps * 60 - drop;
+        let per_ten_minutes = per_minute * 10 + drop;
+        let per_day = per_ten_minutes as i64 * 6 * 24;
+
+        let mut frame = (frame as i64).rem_euclid(per_day) as u32;
+        if drop_frame {
+            let tens = frame / per_ten_minutes;
+            let rest = frame % per_ten_minutes;
+            frame += drop * 9 * tens;
+            if rest > drop {
+                frame += drop * ((rest - drop) / per_minute);
+            }
+        }
+
+        let total_seconds = frame / fps;
+        Ok(Self {
+            hours: total_seconds / 3600,
+            minutes: total_seconds / 60 % 60,
+            seconds: total_seconds % 60,
+            frames: frame % fps,
+            fps,
+            drop_frame,
+        })
+    }
+
+    /// Zero-based frame count this timecode labels.
+    pub fn to_frame(&self) -> i32 {
+        let total_minutes = self.hours * 60 + self.minutes;
+        let total_seconds = total_minutes * 60 + self.seconds;
+        let mut frame = total_seconds as i64 * self.fps as i64 + self.frames as i64;
+        if self.drop_frame {
+            let drop = dropped_per_minute(self.fps).unwrap_or(0);
+            frame -= (drop * (total_minutes - total_minutes / 10)) as i64;
+        }
+        frame as i32
+    }
+
+    /// Parse `HH:MM:SS:FF`. A `;` (or `.`) before the frames field selects drop-frame.
+    pub fn parse(s: &str, fps: u32) -> Result<Self, TimecodeError> {
+        let s = s.trim();
+        let drop_frame = s.contains(';') || s.contains('.');
+        let fields = parse_fields(s)?;
+        let [hours, minutes, seconds, frames] = fields[..] else {
+            return Err(TimecodeError::Format);
+        };
+        Self::new(hours, minutes, seconds, frames, fps, drop_frame)
+    }
+
+    /// Validated constructor.
+    pub fn new(
+        hours: u32,
+        minutes: u32,
+        seconds: u32,
+        frames: u32,
+        fps: u32,
+        drop_frame: bool,
+    ) -> Result<Self, TimecodeError> {
+        let fps = fps.max(1);
+        if hours >= SECONDS_PER_DAY / 3600 || minutes >= 60 || seconds >= 60 || frames >= fps {
+            return Err(TimecodeError::OutOfRange);
+        }
+        if drop_frame {
+            let drop = dropped_per_minute(fps).ok_or(TimecodeError::DropFrameRate(fps))?;
+            if seconds == 0 && !minutes.is_multiple_of(10) && frames < drop {
+                return Err(TimecodeError::DroppedFrame);
+            }
+        }
+        Ok(Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            fps,
+            drop_frame,
+        })
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            ..
+        } = self;
+        let sep = if self.drop_frame { ';' } else { ':' };
+        let pad = (self.fps - 1).to_string().len().max(2);
+        write!(
+            f,
+            "{hours:02}:{minutes:02}:{seconds:02}{sep}{frames:0>pad$}"
+        )
+    }
+}
+
+/// Render `frame` as timecode for `rate`, using drop-frame for 29.97 and 59.94.
+pub fn timecode(frame: i32, rate: FrameRate) -> String {
+    Timecode::from_frame(frame, rate.nominal(), rate.is_drop_frame())
+        .map(|tc| tc.to_string())
+        .unwrap_or_default()
+}
+
+/// Resolve text typed into the timecode field to an absolute zero-based media frame.
+///
+/// Accepts a timecode (`00:01:12:14`, or a shorter right-aligned `1:12:14`), a bare frame
+/// number (`1814`), a time with a unit (`12s`, `1.5m`) or any of those prefixed with `+`/`-`
+/// as an offset from `current`. Absolute timecodes are source timecode, so `start` (the
+/// source frame of media time zero) is subtracted from them.
+pub fn parse_jump(
+    input: &str,
+    current: i32,
+    start: i32,
+    rate: FrameRate,
+) -> Result<i32, TimecodeError> {
+    let input = input.trim();
+    let (sign, body) = match input.as_bytes().first() {
+        Some(b'+') => (Some(1), &input[1..]),
+        Some(b'-') => (Some(-1), &input[1..]),
+        _ => (None, input),
+    };
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(TimecodeError::Format);
+    }
+
+    let frames = if let Some(unit) = body.strip_suffix(['f', 'F']) {
+        if unit.is_empty() || !unit.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(TimecodeError::Format);
+        }
+        unit.parse::<i32>().map_err(|_| TimecodeError::OutOfRange)?
+    } else if let Some((value, scale)) = body
+        .strip_suffix(['s', 'S'])
+        .map(|v| (v, 1.0))
+        .or_else(|| body.strip_suffix(['m', 'M']).map(|v| (v, 60.0)))
+        .or_else(|| body.strip_suffix(['h', 'H']).map(|v| (v, 3600.0)))
+    {
+        let value = value.parse::<f64>().map_err(|_| TimecodeError::Format)?;
+        if !(value.is_finite() && value >= 0.0) {
+            return Err(TimecodeError::Format);
+        }
+        (value * scale * rate.as_f64()).round() as i32
+    } else if body.bytes().all(|b| b.is_ascii_digit()) {
+        body.parse::<i32>().map_err(|_| TimecodeError::OutOfRange)?
+    } else {
+        let fields = parse_fields(body)?;
+        if fields.len() > 4 {
+            return Err(TimecodeError::Format);
+        }
+        let mut padded = [0; 4];
+        padded[4 - fields.len()..].copy_from_slice(&fields);
+        let [hours, minutes, seconds, frames] = padded;
+        // Offsets are durations, so they count plain frames even at drop-frame rates.
+        let drop_frame = sign.is_none() && rate.is_drop_frame();
+        let frame =
+            Timecode::new(hours, minutes, seconds, frames, rate.nominal(), drop_frame)?.to_frame();
+        if sign.is_none() {
+            frame.checked_sub(start).ok_or(TimecodeError::OutOfRange)?
+        } else {
+            frame
+        }
+    };
+
+    Ok(match sign {
+        Some(sign) => {
+            let offset = frames.checked_mul(sign).ok_or(TimecodeError::OutOfRange)?;
+            current.saturating_add(offset)
+        }
+        None => frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tc(frame: i32, fps: u32, drop_frame: bool) -> String {
+        Timecode::from_frame(frame, fps, drop_frame)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn non_drop_formatting() {
+        assert_eq!(tc(0, 25, false), "00:00:00:00");
+        assert_eq!(tc(24, 25, false), "00:00:00:24");
+        assert_eq!(tc(25, 25, false), "00:00:01:00");
+        assert_eq!(tc(25 * 60, 25, false), "00:01:00:00");
+        assert_eq!(tc(25 * 3600, 25, false), "01:00:00:00");
+        assert_eq!(tc(25 * 3600 * 2 + 25 * 61 + 3, 25, false), "02:01:01:03");
+    }
+
+    #[test]
+    fn hours_roll_over_at_sixty_minutes() {
+        // The old helper divided by 360 and showed 10 hours here.
+        assert_eq!(tc(24 * 3600, 24, false), "01:00:00:00");
+        assert_eq!(tc(24 * 3599, 24, false), "00:59:59:00");
+    }
+
+    #[test]
+    fn wraps_at_twenty_four_hours() {
+        assert_eq!(tc(25 * 86400, 25, false), "00:00:00:00");
+        assert_eq!(tc(-1, 25, false), "23:59:59:24");
+    }
+
+    #[test]
+    fn wide_frame_field_for_high_rates() {
+        assert_eq!(tc(5, 120, false), "00:00:00:005");
+        assert_eq!(tc(5, 100, false), "00:00:00:05");
+    }
+
+    #[test]
+    fn drop_frame_skips_first_frames_of_each_minute() {
+        assert_eq!(tc(1799, 30, true), "00:00:59;29");
+        assert_eq!(tc(1800, 30, true), "00:01:00;02");
+        assert_eq!(tc(3597, 30, true), "00:01:59;29");
+        assert_eq!(tc(3598, 30, true), "00:02:00;02");
+    }
+
+    #[test]
+    fn drop_frame_keeps_every_tenth_minute() {
+        assert_eq!(tc(17981, 30, true), "00:09:59;29");
+        assert_eq!(tc(17982, 30, true), "00:10:00;00");
+        assert_eq!(tc(17983, 30, true), "00:10:00;01");
+        assert_eq!(tc(107892, 30, true), "01:00:00;00");
+    }
+
+    #[test]
+    fn drop_frame_59_94() {
+        assert_eq!(tc(3599, 60, true), "00:00:59;59");
+        assert_eq!(tc(3600, 60, true), "00:01:00;04");
+        assert_eq!(tc(215784, 60, true), "01:00:00;00");
+    }
+
+    #[test]
+    fn drop_frame_round_trip() {
+        for fps in [30, 60] {
+            for frame in (0..300_000).step_by(7) {
+                let t = Timecode::from_frame(frame, fps, true).unwrap();
+                assert_eq!(t.to_frame(), frame, "{t} at {fps}");
+                assert_eq!(Timecode::parse(&t.to_string(), fps).unwrap(), t);
+            }
+        }
+    }
+
+    #[test]
+    fn non_drop_round_trip() {
+        for fps in [24, 25, 30, 50, 60] {
+            for frame in (0..300_000).step_by(11) {
+                let t = Timecode::from_frame(frame, fps, false).unwrap();
+                assert_eq!(t.to_frame(), frame);
+                assert_eq!(Timecode::parse(&t.to_string(), fps).unwrap(), t);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Timecode::parse("00:00:00", 25), Err(TimecodeError::Format));
+        assert_eq!(
+            Timecode::parse("00:00:0a:00", 25),
+            Err(TimecodeError::Format)
+        );
+        assert_eq!(
+            Timecode::parse("00:00:00:25", 25),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            Timecode::parse("00:60:00:00", 25),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            Timecode::parse("24:00:00:00", 25),
+            Err(TimecodeError::OutOfRange)
+        );
+        // Large enough to overflow when converted to seconds.
+        assert_eq!(
+            Timecode::parse("1193047:01:00:00", 25),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            parse_jump("1193047:01:00:00", 0, 0, FrameRate::FPS_25),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            Timecode::parse("00:01:00;01", 30),
+            Err(TimecodeError::DroppedFrame)
+        );
+        assert_eq!(
+            Timecode::parse("00:01:00;00", 25),
+            Err(TimecodeError::DropFrameRate(25))
+        );
+        assert!(Timecode::parse("00:10:00;00", 30).is_ok());
+    }
+
+    #[test]
+    fn rate_helper_picks_drop_frame_for_ntsc() {
+        assert_eq!(timecode(1800, FrameRate::FPS_29_97), "00:01:00;02");
+        assert_eq!(timecode(3600, FrameRate::FPS_59_94), "00:01:00;04");
+        assert_eq!(timecode(1800, FrameRate::FPS_30), "00:01:00:00");
+        assert_eq!(timecode(1440, FrameRate::FPS_23_976), "00:01:00:00");
+    }
+
+    #[test]
+    fn jump_to_timecode() {
+        let rate = FrameRate::FPS_25;
+        assert_eq!(parse_jump("00:01:12:14", 0, 0, rate), Ok(25 * 72 + 14));
+        assert_eq!(parse_jump("1:12:14", 0, 0, rate), Ok(25 * 72 + 14));
+        assert_eq!(parse_jump(" 12:14 ", 0, 0, rate), Ok(25 * 12 + 14));
+        assert_eq!(
+            parse_jump("00:01:00;02", 0, 0, FrameRate::FPS_29_97),
+            Ok(1800)
+        );
+        assert_eq!(
+            parse_jump("00:01:00:02", 0, 0, FrameRate::FPS_29_97),
+            Ok(1800)
+        );
+    }
+
+    #[test]
+    fn jump_to_frame_and_time() {
+        let rate = FrameRate::FPS_25;
+        assert_eq!(parse_jump("1814", 0, 0, rate), Ok(1814));
+        assert_eq!(parse_jump("12s", 0, 0, rate), Ok(300));
+        assert_eq!(parse_jump("1.5m", 0, 0, rate), Ok(2250));
+        assert_eq!(parse_jump("10f", 0, 0, rate), Ok(10));
+    }
+
+    #[test]
+    fn jump_relative() {
+        let rate = FrameRate::FPS_25;
+        assert_eq!(parse_jump("+10", 100, 0, rate), Ok(110));
+        assert_eq!(parse_jump("-1s", 100, 0, rate), Ok(75));
+        assert_eq!(parse_jump("+1:00", 100, 0, rate), Ok(125));
+        assert_eq!(
+            parse_jump("-00:00:01:00", 100, 0, FrameRate::FPS_29_97),
+            Ok(70)
+        );
+    }
+
+    #[test]
+    fn jump_from_source_timecode() {
+        let rate = FrameRate::FPS_25;
+        let start = 25 * 3600;
+        assert_eq!(parse_jump("01:00:01:00", 0, start, rate), Ok(25));
+        assert_eq!(parse_jump("+1:00", 0, start, rate), Ok(25));
+        assert_eq!(parse_jump("50", 0, start, rate), Ok(50));
+        assert_eq!(parse_jump("00:59:59:24", 0, start, rate), Ok(-1));
+    }
+
+    #[test]
+    fn jump_errors() {
+        let rate = FrameRate::FPS_25;
+        assert_eq!(parse_jump("", 0, 0, rate), Err(TimecodeError::Format));
+        assert_eq!(parse_jump("+", 0, 0, rate), Err(TimecodeError::Format));
+        assert_eq!(parse_jump("abc", 0, 0, rate), Err(TimecodeError::Format));
+        assert_eq!(
+            parse_jump("1:2:3:4:5", 0, 0, rate),
+            Err(TimecodeError::Format)
+        );
+        assert_eq!(
+            parse_jump("00:00:00:30", 0, 0, rate),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            parse_jump("00:01:00:00", 0, 0, FrameRate::FPS_29_97),
+            Err(TimecodeError::DroppedFrame)
+        );
+        assert_eq!(
+            parse_jump("3000000000f", 0, 0, rate),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(
+            parse_jump("-2147483648f", 0, 0, rate),
+            Err(TimecodeError::OutOfRange)
+        );
+        assert_eq!(parse_jump("-f", 0, 0, rate), Err(TimecodeError::Format));
+    }
+}
@@ -0,0 +1,132 @@
+/// Fewest frames the progress bar can be zoomed in to show.
+pub const MIN_VISIBLE_FRAMES: f64 = 24.0;
+
+/// The stretch of media frames the progress bar spans: all of them, or a zoomed-in window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineView {
+    /// Frame at the left edge; fractional while zoomed.
+    pub start: f64,
+    /// Frames across the bar.
+    pub frames: f64,
+}
+
+impl TimelineView {
+    /// The whole of `total` frames.
+    pub fn whole(total: i32) -> Self {
+        TimelineView {
+            start: 0.0,
+            frames: total.max(1) as f64,
+        }
+    }
+
+    /// This view fitted inside `total` frames, moving or shrinking it as little as possible.
+    pub fn clamped(self, total: i32) -> Self {
+        let total = total.max(1) as f64;
+        let frames = self.frames.clamp(MIN_VISIBLE_FRAMES.min(total), total);
+        TimelineView {
+            start: self.start.clamp(0.0, total - frames),
+            frames,
+        }
+    }
+
+    pub fn is_whole(self, total: i32) -> bool {
+        self.frames >= total as f64
+    }
+
+    /// How many times the media is magnified.
+    pub fn zoom(self, total: i32) -> f64 {
+        total.max(1) as f64 / self.frames
+    }
+
+    /// Frame under `fraction` of the way across the bar.
+    pub fn frame_at(self, fraction: f64) -> i32 {
+        (self.start + fraction * self.frames).floor() as i32
+    }
+
+    /// How far across the bar `frame` starts; outside `0.0..1.0` when out of view.
+    pub fn fraction(self, frame: f64) -> f64 {
+        (frame - self.start) / self.frames
+    }
+
+    /// Magnified by `factor`, keeping the point `anchor` of the way across the bar fixed.
+    pub fn zoomed(self, total: i32, factor: f64, anchor: f64) -> Self {
+        let anchor_frame = self.start + anchor * self.frames;
+        let frames = self.frames / factor;
+        TimelineView {
+            start: anchor_frame - anchor * frames,
+            frames,
+        }
+        .clamped(total)
+    }
+
+    /// Moved by `frames`, positive towards the end.
+    pub fn panned(self, total: i32, frames: f64) -> Self {
+        TimelineView {
+            start: self.start + frames,
+            ..self
+        }
+        .clamped(total)
+    }
+
+    /// Paged so `frame` is in view, as a playhead leaving the window drags it along.
+    pub fn following(self, total: i32, frame: i32) -> Self {
+        let frame = frame as f64;
+        let start = if frame < self.start {
+            frame - self.frames + 1.0
+        } else if frame >= self.start + self.frames {
+            frame
+        } else {
+            return self;
+        };
+        TimelineView { start, ..self }.clamped(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zooms_about_the_anchor() {
+        let whole = TimelineView::whole(1000);
+        assert_eq!(whole.frame_at(0.5), 500);
+        let zoomed = whole.zoomed(1000, 4.0, 0.5);
+        assert_eq!(
+            zoomed,
+            TimelineView {
+                start: 375.0,
+                frames: 250.0
+            }
+        );
+        assert_eq!(zoomed.frame_at(0.5), 500);
+        assert_eq!(zoomed.fraction(375.0), 0.0);
+        assert_eq!(zoomed.zoom(1000), 4.0);
+
+        // Zooming out past the whole, or in past the minimum, stops there.
+        assert!(zoomed.zoomed(1000, 0.1, 0.2).is_whole(1000));
+        assert_eq!(whole.zoomed(1000, 1e6, 0.0).frames, MIN_VISIBLE_FRAMES);
+        assert_eq!(TimelineView::whole(10).zoomed(10, 4.0, 0.0).frames, 10.0);
+    }
+
+    #[test]
+    fn pans_and_follows_within_the_media() {
+        let view = TimelineView {
+            start: 100.0,
+            frames: 100.0,
+        };
+        assert_eq!(view.panned(1000, -500.0).start, 0.0);
+        assert_eq!(view.panned(1000, 5000.0).start, 900.0);
+        assert_eq!(view.following(1000, 150), view);
+        assert_eq!(view.following(1000, 200).start, 200.0);
+        assert_eq!(view.following(1000, 50).start, 0.0);
+        assert_eq!(view.following(1000, 995).start, 900.0);
+        // A shorter media shrinks the window.
+        assert_eq!(
+            view.clamped(60),
+            TimelineView {
+                start: 0.0,
+                frames: 60.0
+            }
+        );
+    }
+}
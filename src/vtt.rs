@@ -0,0 +1,276 @@
+use std::fmt;
+
+/// One cue of a WebVTT file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub id: Option<String>,
+    /// Start time in seconds.
+    pub start: f64,
+    /// End time in seconds.
+    pub end: f64,
+    /// Cue settings after the end timestamp, e.g. `line:0 align:start`.
+    pub settings: String,
+    /// Payload lines joined with `\n`, markup untouched.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VttError {
+    /// The file doesn't start with `WEBVTT`.
+    MissingHeader,
+    /// A cue timing line couldn't be read; 1-based line number.
+    Timing(usize),
+}
+
+impl fmt::Display for VttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "missing WEBVTT header"),
+            Self::Timing(line) => write!(f, "bad cue timing on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for VttError {}
+
+/// Parse `hh:mm:ss.ttt` or `mm:ss.ttt` into seconds. A `,` before the milliseconds is
+/// accepted too, as SRT writes it.
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let (clock, millis) = s.trim().split_once(['.', ','])?;
+    if millis.len() != 3 || !millis.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fields: Vec<&str> = clock.split(':').collect();
+    let (hours, minutes, seconds) = match fields.as_slice() {
+        [m, s] => ("0", *m, *s),
+        [h, m, s] => (*h, *m, *s),
+        _ => return None,
+    };
+    let field = |s: &str, max: u64| {
+        (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| s.parse::<u64>().ok())
+            .flatten()
+            .filter(|&v| v < max)
+    };
+    let hours = field(hours, u64::MAX)?;
+    let minutes = field(minutes, 60)?;
+    let seconds = field(seconds, 60)?;
+    let millis: u64 = millis.parse().ok()?;
+    let whole = hours
+        .checked_mul(3600)?
+        .checked_add(minutes * 60 + seconds)?;
+    Some(whole as f64 + millis as f64 / 1000.0)
+}
+
+/// Format seconds as a WebVTT `hh:mm:ss.ttt` timestamp.
+pub fn format_timestamp(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Parse a `start --> end [settings]` line.
+pub(crate) fn parse_timing(line: &str) -> Option<(f64, f64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_timestamp(start)?,
+        parse_timestamp(end)?,
+        settings.trim().to_string(),
+    ))
+}
+
+/// Parse the cues of a WebVTT file, skipping `NOTE`, `STYLE` and `REGION` blocks.
+pub fn parse(text: &str) -> Result<Vec<Cue>, VttError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = text.lines().enumerate().peekable();
+    match lines.next() {
+        Some((_, header))
+            if header == "WEBVTT"
+                || header.starts_with("WEBVTT ")
+                || header.starts_with("WEBVTT\t") => {}
+        _ => return Err(VttError::MissingHeader),
+    }
+    // The rest of the header block.
+    while lines.next_if(|(_, line)| !line.trim().is_empty()).is_some() {}
+
+    let mut cues = Vec::new();
+    loop {
+        while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+        let Some((number, first)) = lines.next() else {
+            break;
+        };
+        let block = first.split_whitespace().next().unwrap_or("");
+        if matches!(block, "NOTE" | "STYLE" | "REGION") {
+            while lines.next_if(|(_, line)| !line.trim().is_empty()).is_some() {}
+            continue;
+        }
+        let (id, (number, timing)) = if first.contains("-->") {
+            (None, (number, first))
+        } else {
+            match lines.next() {
+                Some(next) => (Some(first.to_string()), next),
+                None => break,
+            }
+        };
+        let (start, end, settings) = parse_timing(timing).ok_or(VttError::Timing(number + 1))?;
+        let mut payload = Vec::new();
+        while let Some((_, line)) = lines.next_if(|(_, line)| !line.trim().is_empty()) {
+            payload.push(line);
+        }
+        cues.push(Cue {
+            id,
+            start,
+            end,
+            settings,
+            text: payload.join("\n"),
+        });
+    }
+    Ok(cues)
+}
+
+/// Parse the cues of a SubRip (`.srt`) file. Cue numbers become ids; SRT has no settings.
+pub fn parse_srt(text: &str) -> Result<Vec<Cue>, VttError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = text.lines().enumerate().peekable();
+    let mut cues = Vec::new();
+    loop {
+        while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+        let Some((number, first)) = lines.next() else {
+            break;
+        };
+        let (id, (number, timing)) = if first.contains("-->") {
+            (None, (number, first))
+        } else {
+            match lines.next() {
+                Some(next) => (Some(first.trim().to_string()), next),
+                None => break,
+            }
+        };
+        let (start, end, _) = parse_timing(timing).ok_or(VttError::Timing(number + 1))?;
+        let mut payload = Vec::new();
+        while let Some((_, line)) = lines.next_if(|(_, line)| !line.trim().is_empty()) {
+            payload.push(line);
+        }
+        cues.push(Cue {
+            id,
+            start,
+            end,
+            settings: String::new(),
+            text: payload.join("\n"),
+        });
+    }
+    Ok(cues)
+}
+
+/// Cue text with markup removed: `<b>`, `<v Name>` and the like, SRT's `{\an8}` overrides,
+/// and the character references WebVTT defines.
+pub fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['<', '{', '&']) {
+        plain.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let close = match rest.as_bytes()[0] {
+            b'<' => rest.find('>'),
+            b'{' if rest.starts_with("{\\") => rest.find('}'),
+            b'&' => rest.find(';').filter(|&end| end <= 6),
+            _ => None,
+        };
+        let Some(close) = close else {
+            plain.push_str(&rest[..1]);
+            rest = &rest[1..];
+            continue;
+        };
+        match &rest[..=close] {
+            "&amp;" => plain.push('&'),
+            "&lt;" => plain.push('<'),
+            "&gt;" => plain.push('>'),
+            "&nbsp;" => plain.push('\u{a0}'),
+            "&lrm;" => plain.push('\u{200e}'),
+            "&rlm;" => plain.push('\u{200f}'),
+            entity if entity.starts_with('&') => plain.push_str(entity),
+            _ => {}
+        }
+        rest = &rest[close + 1..];
+    }
+    plain.push_str(rest);
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("00:01.500"), Some(1.5));
+        assert_eq!(parse_timestamp("01:00:00.000"), Some(3600.0));
+        assert_eq!(parse_timestamp("00:00:02,040"), Some(2.04));
+        assert_eq!(parse_timestamp("00:60.000"), None);
+        assert_eq!(parse_timestamp("00:01.5"), None);
+        assert_eq!(parse_timestamp("1.000"), None);
+        assert_eq!(parse_timestamp("18446744073709551614:00:00.000"), None);
+    }
+
+    #[test]
+    fn timestamp_round_trip() {
+        assert_eq!(format_timestamp(3725.5), "01:02:05.500");
+        for seconds in [0.0, 1.5, 59.999, 3600.0, 86_399.001] {
+            assert_eq!(parse_timestamp(&format_timestamp(seconds)), Some(seconds));
+        }
+    }
+
+    #[test]
+    fn parses_cues_and_skips_blocks() {
+        let text = "\u{feff}WEBVTT - chapters\nKind: chapters\n\nNOTE made by hand\nspans lines\n\nintro\n00:00.000 --> 00:05.000\nIntro\n\n00:05.000 --> 00:01:00.000 align:start\nAct one\nsecond line\n";
+        let cues = parse(text).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].id.as_deref(), Some("intro"));
+        assert_eq!((cues[0].start, cues[0].end), (0.0, 5.0));
+        assert_eq!(cues[1].id, None);
+        assert_eq!(cues[1].end, 60.0);
+        assert_eq!(cues[1].settings, "align:start");
+        assert_eq!(cues[1].text, "Act one\nsecond line");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("1\n00:00.000 --> 00:01.000\n"),
+            Err(VttError::MissingHeader)
+        );
+        assert_eq!(
+            parse("WEBVTT\n\n00:00.000 --> soon\nx\n"),
+            Err(VttError::Timing(3))
+        );
+    }
+
+    #[test]
+    fn parses_srt() {
+        let text = "1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello</i>\r\nthere\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000 X1:10 X2:20\r\n{\\an8}Top\r\n";
+        let cues = parse_srt(text).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].id.as_deref(), Some("1"));
+        assert_eq!((cues[0].start, cues[0].end), (1.0, 2.5));
+        assert_eq!(cues[0].text, "<i>Hello</i>\nthere");
+        assert_eq!(cues[1].settings, "");
+        assert_eq!(
+            parse_srt("1\n00:00:01 --> 00:00:02\nx\n"),
+            Err(VttError::Timing(2))
+        );
+    }
+
+    #[test]
+    fn strips_markup() {
+        assert_eq!(plain_text("<v Bob>Hi</v> <i>there</i>"), "Hi there");
+        assert_eq!(plain_text("{\\an8}Top &amp; &lt;tail&gt;"), "Top & <tail>");
+        assert_eq!(plain_text("a < b & {c}"), "a < b & {c}");
+    }
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "ssr")]
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use leptos_video::media::{parse_range, router, ByteRange};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use tower::ServiceExt;
+
+/// A temporary directory, removed when dropped.
+struct TempRoot(PathBuf);
+
+impl Deref for TempRoot {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempRoot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A media root holding `clip.mp4` with bytes `0, 1, …, 255, 0, 1, …` (1000 of them).
+fn media_root(name: &str) -> TempRoot {
+    let root = std::env::temp_dir().join(format!("media-test-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    std::fs::write(root.join("clip.mp4"), data).unwrap();
+    TempRoot(root)
+}
+
+async fn get(root: &Path, path: &str, headers: &[(header::HeaderName, &str)]) -> Response {
+    let mut request = Request::get(path);
+    for (name, value) in headers {
+        request = request.header(name, *value);
+    }
+    router(root)
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn body(response: Response) -> Vec<u8> {
+    to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap()
+        .to_vec()
+}
+
+fn header(response: &Response, name: header::HeaderName) -> &str {
+    response.headers()[name].to_str().unwrap()
+}
+
+#[test]
+fn range_parsing() {
+    assert_eq!(parse_range("bytes=0-9", 100), ByteRange::Partial(0, 9));
+    assert_eq!(parse_range("bytes=90-", 100), ByteRange::Partial(90, 99));
+    assert_eq!(parse_range("bytes=90-500", 100), ByteRange::Partial(90, 99));
+    assert_eq!(parse_range("bytes=-10", 100), ByteRange::Partial(90, 99));
+    assert_eq!(parse_range("bytes=-500", 100), ByteRange::Partial(0, 99));
+    assert_eq!(parse_range("bytes=100-", 100), ByteRange::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-0", 100), ByteRange::Unsatisfiable);
+    assert_eq!(parse_range("bytes=9-0", 100), ByteRange::Full);
+    assert_eq!(parse_range("bytes=a-b", 100), ByteRange::Full);
+    assert_eq!(parse_range("bytes=0-1,5-6", 100), ByteRange::Full);
+    assert_eq!(parse_range("items=0-1", 100), ByteRange::Full);
+}
+
+#[tokio::test]
+async fn full_file() {
+    let root = media_root("full");
+    let response = get(&root, "/clip.mp4", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, header::CONTENT_TYPE), "video/mp4");
+    assert_eq!(header(&response, header::ACCEPT_RANGES), "bytes");
+    assert_eq!(header(&response, header::CONTENT_LENGTH), "1000");
+    assert!(header(&response, header::CACHE_CONTROL).contains("max-age"));
+    assert_eq!(body(response).await.len(), 1000);
+}
+
+#[tokio::test]
+async fn partial_range() {
+    let root = media_root("partial");
+    let response = get(&root, "/clip.mp4", &[(header::RANGE, "bytes=256-259")]).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        header(&response, header::CONTENT_RANGE),
+        "bytes 256-259/1000"
+    );
+    assert_eq!(header(&response, header::CONTENT_LENGTH), "4");
+    assert_eq!(body(response).await, [0, 1, 2, 3]);
+
+    let response = get(&root, "/clip.mp4", &[(header::RANGE, "bytes=998-")]).await;
+    assert_eq!(
+        header(&response, header::CONTENT_RANGE),
+        "bytes 998-999/1000"
+    );
+    assert_eq!(body(response).await, [230, 231]);
+}
+
+#[tokio::test]
+async fn suffix_range() {
+    let root = media_root("suffix");
+    let response = get(&root, "/clip.mp4", &[(header::RANGE, "bytes=-3")]).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        header(&response, header::CONTENT_RANGE),
+        "bytes 997-999/1000"
+    );
+    assert_eq!(body(response).await, [229, 230, 231]);
+}
+
+#[tokio::test]
+async fn invalid_ranges() {
+    let root = media_root("invalid");
+    let response = get(&root, "/clip.mp4", &[(header::RANGE, "bytes=1000-")]).await;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(header(&response, header::CONTENT_RANGE), "bytes */1000");
+
+    // Malformed ranges are ignored and the whole file is sent.
+    let response = get(&root, "/clip.mp4", &[(header::RANGE, "bytes=oops")]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await.len(), 1000);
+}
+
+#[tokio::test]
+async fn validators() {
+    let root = media_root("validators");
+    let etag = header(&get(&root, "/clip.mp4", &[]).await, header::ETAG).to_string();
+
+    let response = get(&root, "/clip.mp4", &[(header::IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = get(
+        &root,
+        "/clip.mp4",
+        &[(header::RANGE, "bytes=0-1"), (header::IF_RANGE, &etag)],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+
+    // A stale `If-Range` gets the whole, current file.
+    let response = get(
+        &root,
+        "/clip.mp4",
+        &[
+            (header::RANGE, "bytes=0-1"),
+            (header::IF_RANGE, "\"stale\""),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, header::CONTENT_LENGTH), "1000");
+}
+
+#[tokio::test]
+async fn stays_inside_root() {
+    let root = media_root("escape");
+    for path in ["/missing.mp4", "/../clip.mp4", "/%2e%2e/etc/passwd"] {
+        let response = get(&root, path, &[]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+}
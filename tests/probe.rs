@@ -0,0 +1,84 @@
+use leptos_video::frame_rate::FrameRate;
+use leptos_video::probe::{probe, AudioTrack, Mp4Info, ProbeError, VideoTrack};
+use std::fs::File;
+
+fn fixture(name: &str) -> Mp4Info {
+    try_fixture(name).unwrap()
+}
+
+fn try_fixture(name: &str) -> Result<Mp4Info, ProbeError> {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    probe(File::open(path).unwrap())
+}
+
+#[test]
+fn h264_mp4() {
+    let info = fixture("h264_23976.mp4");
+    assert_eq!(info.duration, 10.01);
+    assert_eq!(
+        info.video,
+        Some(VideoTrack {
+            codec: "avc1".into(),
+            width: 1920,
+            height: 1080,
+            fps: FrameRate::FPS_23_976,
+            frame_count: 240,
+        })
+    );
+    assert_eq!(
+        info.audio,
+        [AudioTrack {
+            codec: "mp4a".into(),
+            channels: 2,
+            sample_rate: 48000,
+            language: Some("eng".into()),
+        }]
+    );
+    assert_eq!(info.start_timecode, None);
+}
+
+#[test]
+fn prores_mov_with_timecode() {
+    let info = fixture("prores_2997df_timecode.mov");
+    assert_eq!(info.duration, 10.0);
+    let video = info.video.unwrap();
+    assert_eq!(
+        (video.codec.as_str(), video.width, video.height),
+        ("apcn", 1280, 720)
+    );
+    assert_eq!(video.fps, FrameRate::FPS_29_97);
+    assert_eq!(video.frame_count, 300);
+
+    let start = info.start_timecode.unwrap();
+    assert_eq!(start.frame, 107_892);
+    assert_eq!(start.rate, FrameRate::FPS_29_97);
+    assert_eq!(start.to_string(), "01:00:00;00");
+
+    assert_eq!(info.audio.len(), 2);
+    assert_eq!(
+        (
+            info.audio[0].codec.as_str(),
+            info.audio[0].channels,
+            info.audio[0].sample_rate
+        ),
+        ("lpcm", 6, 48000)
+    );
+    assert_eq!(info.audio[0].language.as_deref(), Some("fra"));
+    assert_eq!(
+        (
+            info.audio[1].codec.as_str(),
+            info.audio[1].channels,
+            info.audio[1].sample_rate
+        ),
+        ("sowt", 2, 44100)
+    );
+    assert_eq!(info.audio[1].language, None);
+}
+
+#[test]
+fn box_larger_than_a_seek() {
+    assert!(matches!(
+        try_fixture("oversized_mdat.mp4"),
+        Err(ProbeError::Malformed("box"))
+    ));
+}